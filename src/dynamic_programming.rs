@@ -0,0 +1,107 @@
+use crate::machine_learning::{ChordInput, Realisation, SolveError};
+use crate::music_theory::*;
+use crate::scoring::Scorer;
+
+// finds the realisation with the best possible score, by going through every voicing of every
+// chord and keeping, for each voicing and the voicing of the chord before it, the best way of
+// reaching that pair from the start (the viterbi algorithm). this works because `Scorer::score`
+// never looks at more than three chords in a row.
+pub fn best_realisation(
+    chords_input: &[ChordInput],
+    scorer: &Scorer,
+) -> Result<Realisation, SolveError> {
+    let all_voicings = Realisation::all_voicings(chords_input)?;
+
+    if all_voicings.len() == 1 {
+        let mut best: Option<Realisation> = None;
+        for chord in all_voicings[0].iter() {
+            let score = scorer.chord_score(chords_input, 0, chord);
+            if best.as_ref().is_none_or(|best| score > best.score) {
+                best = Some(Realisation {
+                    chords: vec![*chord],
+                    score,
+                });
+            }
+        }
+        return Ok(best.expect("every chord has at least one voicing"));
+    }
+
+    // the best score of any realisation up to and including each pair of voicings of the
+    // previous and current chord, indexed by the previous voicing and then the current one
+    let mut best_scores: Vec<Vec<i32>> = all_voicings[0]
+        .iter()
+        .map(|first_chord| {
+            let first_score = scorer.chord_score(chords_input, 0, first_chord);
+            all_voicings[1]
+                .iter()
+                .map(|chord| {
+                    first_score
+                        + scorer.transition_score(chords_input, 1, first_chord, chord)
+                        + scorer.chord_score(chords_input, 1, chord)
+                })
+                .collect()
+        })
+        .collect();
+    // for each chord after the second, which voicing of the chord two before each pair came from
+    let mut back_pointers: Vec<Vec<Vec<usize>>> = Vec::with_capacity(all_voicings.len() - 2);
+
+    for i in 2..all_voicings.len() {
+        let first_voicings = &all_voicings[i - 2];
+        let prev_voicings = &all_voicings[i - 1];
+        let mut scores = Vec::with_capacity(prev_voicings.len());
+        let mut pointers = Vec::with_capacity(prev_voicings.len());
+        for (j, prev_chord) in prev_voicings.iter().enumerate() {
+            let mut prev_scores = Vec::with_capacity(all_voicings[i].len());
+            let mut prev_pointers = Vec::with_capacity(all_voicings[i].len());
+            for chord in all_voicings[i].iter() {
+                let mut best_first = 0;
+                let mut best_score = i32::MIN;
+                for (h, first_chord) in first_voicings.iter().enumerate() {
+                    let score = best_scores[h][j]
+                        + scorer.three_chord_score(
+                            chords_input,
+                            i,
+                            [first_chord, prev_chord, chord],
+                        );
+                    if score > best_score {
+                        best_score = score;
+                        best_first = h;
+                    }
+                }
+                prev_scores.push(
+                    best_score
+                        + scorer.transition_score(chords_input, i, prev_chord, chord)
+                        + scorer.chord_score(chords_input, i, chord),
+                );
+                prev_pointers.push(best_first);
+            }
+            scores.push(prev_scores);
+            pointers.push(prev_pointers);
+        }
+        best_scores = scores;
+        back_pointers.push(pointers);
+    }
+
+    // start from the best pair of voicings of the last two chords and follow the pointers back
+    let (mut prev_index, mut index) = (0, 0);
+    for (j, scores) in best_scores.iter().enumerate() {
+        for (k, score) in scores.iter().enumerate() {
+            if *score > best_scores[prev_index][index] {
+                (prev_index, index) = (j, k);
+            }
+        }
+    }
+    let score = best_scores[prev_index][index];
+    let last = all_voicings.len() - 1;
+    let mut chords: Vec<Chord> = Vec::with_capacity(all_voicings.len());
+    chords.push(all_voicings[last][index]);
+    chords.push(all_voicings[last - 1][prev_index]);
+    for (i, pointers) in back_pointers.iter().enumerate().rev() {
+        let first_index = pointers[prev_index][index];
+        chords.push(all_voicings[i][first_index]);
+        (prev_index, index) = (first_index, prev_index);
+    }
+    chords.reverse();
+
+    Ok(Realisation { chords, score })
+}
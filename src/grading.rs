@@ -0,0 +1,204 @@
+use crate::machine_learning::ChordInput;
+use crate::music_theory::*;
+use crate::scoring::{Report, Scorer, Voice};
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GradeError {
+    // there has to be a chord for every bass note
+    WrongNumberOfChords { figures: usize, chords: usize },
+}
+impl Display for GradeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GradeError::WrongNumberOfChords { figures, chords } => write!(
+                f,
+                "there are {figures} bass notes in the exercise but {chords} chords in the realisation"
+            ),
+        }
+    }
+}
+impl std::error::Error for GradeError {}
+
+// a chord that doesn't have the notes the figures ask for
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mistake {
+    // the bass isn't the one in the exercise
+    WrongBass {
+        chord_index: usize,
+        expected: NoteWithOctave,
+        found: NoteWithOctave,
+    },
+    // a voice has a note that isn't in the chord
+    WrongNote {
+        chord_index: usize,
+        voice: Voice,
+        note: NoteWithOctave,
+    },
+    // none of the voices have one of the notes in the chord
+    MissingNote {
+        chord_index: usize,
+        note: Note,
+    },
+}
+impl Mistake {
+    pub fn chord_index(&self) -> usize {
+        match self {
+            Mistake::WrongBass { chord_index, .. }
+            | Mistake::WrongNote { chord_index, .. }
+            | Mistake::MissingNote { chord_index, .. } => *chord_index,
+        }
+    }
+}
+impl Display for Mistake {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Mistake::WrongBass {
+                chord_index,
+                expected,
+                found,
+            } => write!(
+                f,
+                "chord {}, bass: {found} should be {expected}",
+                chord_index + 1
+            ),
+            Mistake::WrongNote {
+                chord_index,
+                voice,
+                note,
+            } => write!(
+                f,
+                "chord {}, {voice}: {note} isn't in the chord",
+                chord_index + 1
+            ),
+            Mistake::MissingNote { chord_index, note } => {
+                write!(f, "chord {}: missing {note}", chord_index + 1)
+            }
+        }
+    }
+}
+
+// the result of checking a realisation against the exercise it is meant to realise
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grading {
+    pub mistakes: Vec<Mistake>,
+    // what the scorer made of the part writing
+    pub report: Report,
+    // how many chords have no mistakes or penalties
+    pub correct_chords: usize,
+    pub total_chords: usize,
+}
+impl Grading {
+    pub fn percentage(&self) -> u32 {
+        (self.correct_chords * 100 / self.total_chords) as u32
+    }
+}
+impl Display for Grading {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // the mistakes and penalties together, in the order of the chords
+        for chord_index in 0..self.total_chords {
+            for mistake in self.mistakes.iter() {
+                if mistake.chord_index() == chord_index {
+                    writeln!(f, "{mistake}")?;
+                }
+            }
+            for finding in self.report.penalties() {
+                if finding.chord_index == chord_index {
+                    writeln!(f, "{finding}")?;
+                }
+            }
+        }
+        write!(
+            f,
+            "{}/{} chords correct ({}%)",
+            self.correct_chords,
+            self.total_chords,
+            self.percentage()
+        )
+    }
+}
+
+// the rules from `Scorer::default` that are mistakes rather than preferences of the solvers, for
+// use with `grade`
+pub fn grading_scorer() -> Scorer {
+    let mut scorer = Scorer::default();
+    scorer.remove("melodic motion");
+    scorer.remove("comfortable range");
+    scorer
+}
+
+fn pitch_class(note: Note) -> u32 {
+    note.to_semitones() % 12
+}
+
+// checks that every chord has the bass from the exercise and exactly the notes its figures ask
+// for (with the sixth and seventh degrees of minor keys from `minor_scale`), and scores the part
+// writing, where a chord is only correct if it has no mistakes and no penalties from the scorer
+pub fn grade(
+    key_sig: KeySignature,
+    figures: &[Figure],
+    chords: &[Chord],
+    minor_scale: MinorScale,
+    scorer: &Scorer,
+) -> Result<Grading, GradeError> {
+    if figures.len() != chords.len() || chords.is_empty() {
+        return Err(GradeError::WrongNumberOfChords {
+            figures: figures.len(),
+            chords: chords.len(),
+        });
+    }
+    let chords_input = ChordInput::from_figures(figures, key_sig, minor_scale);
+
+    let mut mistakes = Vec::new();
+    for (chord_index, (chord, input)) in chords.iter().zip(chords_input.iter()).enumerate() {
+        if chord.b.to_semitones() != input.bass.to_semitones() {
+            mistakes.push(Mistake::WrongBass {
+                chord_index,
+                expected: input.bass,
+                found: chord.b,
+            });
+        }
+        let voices = [Voice::Soprano, Voice::Alto, Voice::Tenor, Voice::Bass];
+        for voice in voices {
+            let note = voice.note(chord);
+            if !input
+                .possible_notes()
+                .iter()
+                .any(|x| pitch_class(*x) == pitch_class(note.note))
+            {
+                mistakes.push(Mistake::WrongNote {
+                    chord_index,
+                    voice,
+                    note,
+                });
+            }
+        }
+        for note in input.notes.iter() {
+            let forms = input.forms(*note);
+            if !chord
+                .voices()
+                .iter()
+                .any(|x| forms.iter().any(|y| pitch_class(x.note) == pitch_class(*y)))
+            {
+                mistakes.push(Mistake::MissingNote {
+                    chord_index,
+                    note: *note,
+                });
+            }
+        }
+    }
+
+    let report = scorer.report(&chords_input, chords);
+    let correct_chords = (0..chords.len())
+        .filter(|i| {
+            !mistakes.iter().any(|x| x.chord_index() == *i)
+                && !report.penalties().any(|x| x.chord_index == *i)
+        })
+        .count();
+    Ok(Grading {
+        mistakes,
+        report,
+        correct_chords,
+        total_chords: chords.len(),
+    })
+}
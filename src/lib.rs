@@ -0,0 +1,140 @@
+// finds the best four-part (SATB) realisation of a figured bass, along with readers and writers
+// for the file formats that exercises and realisations are stored in
+pub mod dynamic_programming;
+pub mod grading;
+pub mod lilypond;
+pub mod machine_learning;
+pub mod midi;
+pub mod music_theory;
+pub mod musicxml;
+pub mod rules;
+pub mod scoring;
+
+pub use grading::{grade, GradeError, Grading, Mistake};
+pub use machine_learning::{ChordInput, MachineLearning, Realisation, SolveError};
+pub use music_theory::{
+    parse_exercise, Accidental, Chord, Figure, Interval, KeySignature, MinorScale, Note, NoteName,
+    NoteWithOctave, ParseError, Quality, Tonality,
+};
+use rand::Rng;
+pub use scoring::{Context, Finding, Report, Rule, Scorer, Voice};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Solver {
+    // the genetic algorithm in `machine_learning`, which is random and may miss the best answer
+    Genetic,
+    // the search in `dynamic_programming`, which always finds the best realisation
+    Exact,
+}
+
+#[derive(Debug, Clone)]
+pub struct SolverOptions {
+    pub solver: Solver,
+    pub scorer: Scorer,
+    // how to fill in the sixth and seventh degrees of minor keys that the figures leave out
+    pub minor_scale: MinorScale,
+    // the rest of the options only apply to the genetic algorithm
+    pub generations: u32,
+    pub population: u32,
+    // the proportions of each generation that are carried over unchanged, or mutated three or
+    // two times, from the best realisations of the previous generation
+    pub non_mutated_percentage: f32,
+    pub mutate_thrice_percentage: f32,
+    pub mutate_twice_percentage: f32,
+    // the same seed and input always give the same realisation
+    pub seed: Option<u64>,
+    // whether to print the best score of each generation to stderr
+    pub print_progress: bool,
+}
+impl Default for SolverOptions {
+    fn default() -> Self {
+        Self {
+            solver: Solver::Genetic,
+            scorer: Scorer::default(),
+            minor_scale: MinorScale::Natural,
+            generations: 100,
+            population: 2000,
+            non_mutated_percentage: 0.1,
+            mutate_thrice_percentage: 0.2,
+            mutate_twice_percentage: 0.1,
+            seed: None,
+            print_progress: false,
+        }
+    }
+}
+
+fn machine_learning(options: &SolverOptions) -> MachineLearning {
+    let mut ml = MachineLearning::new(
+        options.generations,
+        options.population,
+        options.non_mutated_percentage,
+        options.mutate_thrice_percentage,
+        options.mutate_twice_percentage,
+    );
+    ml.print_progress = options.print_progress;
+    ml.seed = options.seed;
+    ml.scorer = options.scorer.clone();
+    ml
+}
+
+// realises the figured bass in the given key
+pub fn solve(
+    key_sig: KeySignature,
+    figures: &[Figure],
+    options: &SolverOptions,
+) -> Result<Realisation, SolveError> {
+    let chords_input = ChordInput::from_figures(figures, key_sig, options.minor_scale);
+    match options.solver {
+        Solver::Genetic => machine_learning(options).start(&chords_input),
+        Solver::Exact => dynamic_programming::best_realisation(&chords_input, &options.scorer),
+    }
+}
+
+// realises the figured bass with every random decision taken from `rng`, ignoring `options.seed`
+pub fn solve_with_rng(
+    rng: &mut impl Rng,
+    key_sig: KeySignature,
+    figures: &[Figure],
+    options: &SolverOptions,
+) -> Result<Realisation, SolveError> {
+    let chords_input = ChordInput::from_figures(figures, key_sig, options.minor_scale);
+    match options.solver {
+        Solver::Genetic => machine_learning(options).start_with_rng(rng, &chords_input),
+        Solver::Exact => dynamic_programming::best_realisation(&chords_input, &options.scorer),
+    }
+}
+
+pub struct Comparison {
+    pub genetic: Realisation,
+    pub exact: Realisation,
+}
+impl Comparison {
+    // how many points the genetic algorithm's realisation is short of the best possible score
+    pub fn gap(&self) -> i32 {
+        self.exact.score - self.genetic.score
+    }
+}
+
+// realises the figured bass with both solvers, to see how close the genetic algorithm gets
+pub fn compare_solvers(
+    key_sig: KeySignature,
+    figures: &[Figure],
+    options: &SolverOptions,
+) -> Result<Comparison, SolveError> {
+    let chords_input = ChordInput::from_figures(figures, key_sig, options.minor_scale);
+    Ok(Comparison {
+        exact: dynamic_programming::best_realisation(&chords_input, &options.scorer)?,
+        genetic: machine_learning(options).start(&chords_input)?,
+    })
+}
+
+// every penalty and bonus the scorer in `options` gives a realisation of the figured bass
+pub fn report(
+    key_sig: KeySignature,
+    figures: &[Figure],
+    realisation: &Realisation,
+    options: &SolverOptions,
+) -> Report {
+    let chords_input = ChordInput::from_figures(figures, key_sig, options.minor_scale);
+    options.scorer.report(&chords_input, &realisation.chords)
+}
@@ -0,0 +1,130 @@
+use crate::machine_learning::Realisation;
+use crate::music_theory::*;
+use std::fmt::Write;
+
+// lilypond's default (dutch) note names, e.g. fis for F# and bes for Bb
+fn pitch_name(note: Note) -> String {
+    let name: &str = note.name.into();
+    let suffix = match note.accidental {
+        Some(Accidental::DoubleFlat) => "eses",
+        Some(Accidental::Flat) => "es",
+        Some(Accidental::Natural) | None => "",
+        Some(Accidental::Sharp) => "is",
+        Some(Accidental::DoubleSharp) => "isis",
+    };
+    name.to_lowercase() + suffix
+}
+
+// lilypond's absolute octave marks, where c is the C below middle C
+fn octave_marks(octave: u32) -> String {
+    if octave >= 3 {
+        "'".repeat((octave - 3) as usize)
+    } else {
+        ",".repeat((3 - octave) as usize)
+    }
+}
+
+fn figure_accidental(accidental: Accidental) -> &'static str {
+    match accidental {
+        Accidental::DoubleFlat => "--",
+        Accidental::Flat => "-",
+        Accidental::Natural => "!",
+        Accidental::Sharp => "+",
+        Accidental::DoubleSharp => "++",
+    }
+}
+
+fn write_voice(ly: &mut String, name: &str, notes: impl Iterator<Item = NoteWithOctave>) {
+    let _ = write!(ly, "{name} = {{\n  \\global\n ");
+    let mut count = 0;
+    for note in notes {
+        let _ = write!(
+            ly,
+            " {}{}4",
+            pitch_name(note.note),
+            octave_marks(note.octave)
+        );
+        count += 1;
+        if count % CHORDS_PER_MEASURE == 0 {
+            ly.push_str(" |\n ");
+        }
+    }
+    // pad the last bar so that the final barline is in the right place
+    while count % CHORDS_PER_MEASURE != 0 {
+        ly.push_str(" r4");
+        count += 1;
+    }
+    ly.push_str(" \\bar \"|.\"\n}\n\n");
+}
+
+fn write_figures(ly: &mut String, figures: &[Figure]) {
+    // `\figures` is a lilypond keyword, so the variable can't have that name
+    ly.push_str("figuredBass = \\figuremode {\n ");
+    for (i, figure) in figures.iter().enumerate() {
+        if figure.figures.is_empty() {
+            ly.push_str(" <_>4");
+        } else if let Some(accidental) = figure.lone_accidental() {
+            let _ = write!(ly, " <_{}>4", figure_accidental(accidental));
+        } else {
+            // e.g. `<6 4>`, with the highest figure first
+            let stacked: Vec<String> = figure
+                .figures
+                .iter()
+                .rev()
+                .map(|&(number, accidental)| {
+                    number.to_string() + accidental.map_or("", figure_accidental)
+                })
+                .collect();
+            let _ = write!(ly, " <{}>4", stacked.join(" "));
+        }
+        if (i + 1) % CHORDS_PER_MEASURE == 0 {
+            ly.push_str(" |\n ");
+        }
+    }
+    ly.push_str("\n}\n\n");
+}
+
+// writes the realisation as a lilypond score, with soprano and alto on the treble staff, tenor
+// and bass on the bass staff, and the original figures underneath
+pub fn to_lilypond(realisation: &Realisation, figures: &[Figure], key_sig: KeySignature) -> String {
+    let mut ly = String::new();
+    ly.push_str("\\version \"2.24.0\"\n\n");
+
+    let mode: &str = key_sig.tonality.into();
+    let _ = write!(
+        ly,
+        "global = {{\n  \\key {} \\{mode}\n  \\time {CHORDS_PER_MEASURE}/4\n}}\n\n",
+        pitch_name(key_sig.get_starting_note())
+    );
+
+    let voice_names = ["soprano", "alto", "tenor", "bass"];
+    for (i, name) in voice_names.iter().enumerate() {
+        write_voice(
+            &mut ly,
+            name,
+            realisation.chords.iter().map(|chord| chord.voices()[i]),
+        );
+    }
+    write_figures(&mut ly, figures);
+
+    ly.push_str(
+        "\\score {
+  \\new ChoirStaff <<
+    \\new Staff <<
+      \\clef treble
+      \\new Voice = \"soprano\" { \\voiceOne \\soprano }
+      \\new Voice = \"alto\" { \\voiceTwo \\alto }
+    >>
+    \\new Staff <<
+      \\clef bass
+      \\new Voice = \"tenor\" { \\voiceOne \\tenor }
+      \\new Voice = \"bass\" { \\voiceTwo \\bass }
+    >>
+    \\new FiguredBass \\figuredBass
+  >>
+  \\layout { }
+}
+",
+    );
+    ly
+}
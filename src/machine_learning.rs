@@ -1,16 +1,165 @@
 use crate::music_theory::*;
-use rand::Rng;
+use crate::scoring::Scorer;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fmt::Display;
 
-#[derive(Clone)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SolveError {
+    // there were no chords to realise
+    Empty,
+    // the chord at this index has no voicing that fits the notes and part ranges
+    NoVoicing {
+        chord_index: usize,
+        bass: NoteWithOctave,
+    },
+    // the genetic algorithm can't run with these options, for the given reason
+    InvalidOptions(&'static str),
+}
+impl Display for SolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolveError::Empty => write!(f, "there are no chords to realise"),
+            SolveError::NoVoicing { chord_index, bass } => write!(
+                f,
+                "chord {} (bass {}) cannot be voiced in four parts within the part ranges",
+                chord_index + 1,
+                bass
+            ),
+            SolveError::InvalidOptions(reason) => write!(f, "invalid solver options: {reason}"),
+        }
+    }
+}
+impl std::error::Error for SolveError {}
+
+// the bass note of a chord and the notes that have to be in it, along with what the scoring
+// needs to know about the chord's harmony
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChordInput {
+    pub bass: NoteWithOctave,
+    pub notes: Vec<Note>,
+    pub key_sig: KeySignature,
+    // the form of the minor scale the notes were taken from
+    pub minor_scale: MinorScale,
+    // the seventh of the chord, if it is a seventh chord
+    pub seventh: Option<Note>,
+    pub preferred_doubling: Option<Note>,
+    // chromatic notes, which shouldn't be doubled
+    pub altered_notes: Vec<Note>,
+    // notes raised by an accidental in the figures, which should rise like a leading tone (but
+    // not the raised forms in `either_form`, which can go either way)
+    pub raised_notes: Vec<Note>,
+    // with the melodic minor scale, the sixth and seventh degrees the figures leave to the key,
+    // as (natural, raised) pairs, which an upper voice can have in either form depending on which
+    // way it goes next
+    pub either_form: Vec<(Note, Note)>,
+}
+impl ChordInput {
+    // `ascending` is whether the bass is rising at this chord, which only matters for the melodic
+    // minor scale
+    pub fn new(
+        figure: &Figure,
+        key_sig: KeySignature,
+        minor_scale: MinorScale,
+        ascending: bool,
+    ) -> Self {
+        let notes = figure.to_notes_for(key_sig, minor_scale, ascending);
+        let scale = key_sig.get_scale();
+        let mut either_form = Vec::new();
+        if minor_scale == MinorScale::Melodic && key_sig.tonality == Tonality::Minor {
+            for (figure, note) in figure.expanded_figures().iter().zip(notes[1..].iter()) {
+                // the bass is given, so doubling it has to keep its form
+                if figure.1.is_some() || note.name == notes[0].name {
+                    continue;
+                }
+                for natural in scale[5..].iter() {
+                    let forms = (*natural, natural.altered(1));
+                    if note.name == natural.name && !either_form.contains(&forms) {
+                        either_form.push(forms);
+                    }
+                }
+            }
+        }
+        let mut altered_notes = figure.altered_notes(key_sig, &notes);
+        for (_, raised) in either_form.iter() {
+            if !altered_notes.contains(raised) {
+                altered_notes.push(*raised);
+            }
+        }
+        Self {
+            bass: figure.bass,
+            key_sig,
+            minor_scale,
+            seventh: figure.seventh(&notes),
+            preferred_doubling: figure.preferred_doubling(key_sig),
+            altered_notes,
+            raised_notes: figure.raised_notes(key_sig, &notes),
+            notes,
+            either_form,
+        }
+    }
+    // the forms of a note in the chord that a voice can have, which is both forms of the sixth and
+    // seventh degrees in `either_form` and just the note itself otherwise
+    pub fn forms(&self, note: Note) -> Vec<Note> {
+        match self.either_form.iter().find(|x| x.0 == note || x.1 == note) {
+            Some((natural, raised)) => vec![*natural, *raised],
+            None => vec![note],
+        }
+    }
+    // every note an upper voice can have
+    pub fn possible_notes(&self) -> Vec<Note> {
+        let mut possible = self.notes.clone();
+        for (natural, raised) in self.either_form.iter() {
+            for note in [natural, raised] {
+                if !possible.contains(note) {
+                    possible.push(*note);
+                }
+            }
+        }
+        possible
+    }
+    // the input for every chord of a figured bass. with the melodic minor scale, `notes` has the
+    // forms of the sixth and seventh degrees for the bass line, which counts as rising at a chord
+    // unless the next different bass note is a step below it (or, at the end, the one before was a
+    // step above), and the upper voices can have either form.
+    pub fn from_figures(
+        figures: &[Figure],
+        key_sig: KeySignature,
+        minor_scale: MinorScale,
+    ) -> Vec<Self> {
+        figures
+            .iter()
+            .enumerate()
+            .map(|(i, figure)| {
+                let bass = figure.bass;
+                let different = |x: &&Figure| x.bass.to_semitones() != bass.to_semitones();
+                let next = figures[i + 1..].iter().find(different);
+                let prev = figures[..i].iter().rev().find(different);
+                let ascending = match (next, prev) {
+                    (Some(next), _) => bass.steps_up_to(next.bass) != -1,
+                    (None, Some(prev)) => prev.bass.steps_up_to(bass) != -1,
+                    (None, None) => true,
+                };
+                Self::new(figure, key_sig, minor_scale, ascending)
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Realisation {
     pub chords: Vec<Chord>,
     pub score: i32,
 }
 impl Realisation {
     // generates random voicings from the collections of notes and the bassline
-    fn rand_note(lb: &NoteWithOctave, possibles: &Vec<Note>) -> NoteWithOctave {
-        let note_index = rand::thread_rng().gen_range(0..possibles.len());
-        let note = possibles[note_index];
+    fn rand_note(rng: &mut impl Rng, lb: &NoteWithOctave, possibles: &[Note]) -> NoteWithOctave {
+        let note_index = rng.gen_range(0..possibles.len());
+        Self::note_above(lb, possibles[note_index])
+    }
+
+    // the lowest octave of the note that is at or above the lower bound
+    fn note_above(lb: &NoteWithOctave, note: Note) -> NoteWithOctave {
         let mut octave = lb.octave;
         if ((NoteWithOctave::from_note(note, octave).to_semitones() as i32)
             - lb.to_semitones() as i32)
@@ -21,144 +170,114 @@ impl Realisation {
         NoteWithOctave::from_note(note, octave)
     }
 
-    fn generate_chord(chord_input: &(NoteWithOctave, Vec<Note>)) -> Chord {
-        'finding_chord: loop {
-            let b = chord_input.0;
-            let t = Self::rand_note(&b, &chord_input.1);
-            let a = Self::rand_note(&t, &chord_input.1);
-            let s = Self::rand_note(&a, &chord_input.1);
-
-            // all the notes in the figures in the parts
-            for possibility in chord_input.1.iter() {
-                if !(s.note == *possibility
-                    || a.note == *possibility
-                    || t.note == *possibility
-                    || b.note == *possibility)
-                {
-                    // dbg!("all the notes");
-                    continue 'finding_chord;
-                }
+    fn is_valid_chord(chord: &Chord, chord_input: &ChordInput) -> bool {
+        let voices = chord.voices();
+        // all the notes in the figures in the parts, in either form if they have two
+        for note in chord_input.notes.iter() {
+            let forms = chord_input.forms(*note);
+            if !voices.iter().any(|x| forms.contains(&x.note)) {
+                return false;
             }
-
-            // all parts in range
-            let sr = s.in_range_inclusive(
-                NoteWithOctave::new(NoteName::C, None, 4),
-                NoteWithOctave::new(NoteName::G, None, 5),
-            );
-            let ar = a.in_range_inclusive(
-                NoteWithOctave::new(NoteName::G, None, 3),
-                NoteWithOctave::new(NoteName::C, None, 5),
-            );
-            let tr = t.in_range_inclusive(
-                NoteWithOctave::new(NoteName::C, None, 3),
-                NoteWithOctave::new(NoteName::G, None, 4),
-            );
-            if !sr || !ar || !tr {
-                // dbg!("all parts in range");
-                continue 'finding_chord;
+        }
+        // but not both forms in the same chord
+        for (natural, raised) in chord_input.either_form.iter() {
+            if voices.iter().any(|x| x.note == *natural) && voices.iter().any(|x| x.note == *raised)
+            {
+                return false;
             }
-            // we have now found a valid chord, so break
-            break Chord::new(s, a, t, b);
         }
-    }
 
-    pub fn score(chords: &[Chord]) -> i32 {
-        let mut score = 0;
-        let mut prev_chord: Option<Chord> = None;
-        for chord in chords.iter() {
-            // penalise doubling notes in parts
-            if chord.b == chord.t || chord.t == chord.a || chord.a == chord.s {
-                score += -25;
-            }
+        // all parts in range
+        let sr = chord.s.in_range_inclusive(
+            NoteWithOctave::new(NoteName::C, None, 4),
+            NoteWithOctave::new(NoteName::G, None, 5),
+        );
+        let ar = chord.a.in_range_inclusive(
+            NoteWithOctave::new(NoteName::G, None, 3),
+            NoteWithOctave::new(NoteName::C, None, 5),
+        );
+        let tr = chord.t.in_range_inclusive(
+            NoteWithOctave::new(NoteName::C, None, 3),
+            NoteWithOctave::new(NoteName::G, None, 4),
+        );
+        sr && ar && tr
+    }
 
-            if let Some(prev_chord) = prev_chord {
-                let s_interval = prev_chord.s.semitones_between(chord.s) as i32;
-                let a_interval = prev_chord.a.semitones_between(chord.a) as i32;
-                let t_interval = prev_chord.t.semitones_between(chord.t) as i32;
-
-                // give points for small intervals, deduct points for larger ones
-                score += (s_interval * -10) + 40;
-                score += (a_interval * -5) + 20;
-                score += (t_interval * -5) + 20;
-
-                // penalise parallel octaves
-                let b_a =
-                    (prev_chord.b.note == prev_chord.a.note) && (chord.b.note == chord.a.note);
-                let b_s =
-                    (prev_chord.b.note == prev_chord.s.note) && (chord.b.note == chord.s.note);
-                let b_t =
-                    (prev_chord.b.note == prev_chord.t.note) && (chord.b.note == chord.t.note);
-                let t_a =
-                    (prev_chord.t.note == prev_chord.a.note) && (chord.t.note == chord.a.note);
-                let t_s =
-                    (prev_chord.t.note == prev_chord.s.note) && (chord.t.note == chord.s.note);
-                let a_s =
-                    (prev_chord.a.note == prev_chord.s.note) && (chord.a.note == chord.s.note);
-                if b_t || b_a || b_s || t_a || t_s || a_s {
-                    score += -100;
-                }
+    fn generate_chord(rng: &mut impl Rng, chord_input: &ChordInput) -> Chord {
+        loop {
+            let b = chord_input.bass;
+            let possible_notes = chord_input.possible_notes();
+            let t = Self::rand_note(rng, &b, &possible_notes);
+            let a = Self::rand_note(rng, &t, &possible_notes);
+            let s = Self::rand_note(rng, &a, &possible_notes);
 
-                // penalise parallel 5ths
-                let b_t = (prev_chord.b.semitones_up_to(prev_chord.t) == 7)
-                    && chord.b.semitones_up_to(chord.t) == 7;
-                let b_a = (prev_chord.b.semitones_up_to(prev_chord.a) == 7)
-                    && chord.b.semitones_up_to(chord.a) == 7;
-                let b_s = (prev_chord.b.semitones_up_to(prev_chord.s) == 7)
-                    && chord.b.semitones_up_to(chord.s) == 7;
-                let t_a = (prev_chord.t.semitones_up_to(prev_chord.a) == 7)
-                    && chord.t.semitones_up_to(chord.a) == 7;
-                let t_s = (prev_chord.t.semitones_up_to(prev_chord.s) == 7)
-                    && chord.t.semitones_up_to(chord.s) == 7;
-                let a_s = (prev_chord.a.semitones_up_to(prev_chord.s) == 7)
-                    && chord.a.semitones_up_to(chord.s) == 7;
-                if b_t || b_a || b_s || t_a || t_s || a_s {
-                    score += -100;
-                }
+            let chord = Chord::new(s, a, t, b);
+            if Self::is_valid_chord(&chord, chord_input) {
+                // we have now found a valid chord, so break
+                break chord;
             }
+        }
+    }
 
-            if !chord.s.in_range_inclusive(
-                NoteWithOctave::new(NoteName::E, None, 4),
-                NoteWithOctave::new(NoteName::E, None, 5),
-            ) {
-                score += -5;
-            }
-            if !chord.a.in_range_inclusive(
-                NoteWithOctave::new(NoteName::B, None, 3),
-                NoteWithOctave::new(NoteName::B, None, 4),
-            ) {
-                score += -5;
-            }
-            if !chord.t.in_range_inclusive(
-                NoteWithOctave::new(NoteName::G, None, 3),
-                NoteWithOctave::new(NoteName::G, None, 4),
-            ) {
-                score += -5;
+    // the voicings of every chord, or an error if any of them can't be voiced
+    pub fn all_voicings(chords_input: &[ChordInput]) -> Result<Vec<Vec<Chord>>, SolveError> {
+        if chords_input.is_empty() {
+            return Err(SolveError::Empty);
+        }
+        let mut all_voicings = Vec::with_capacity(chords_input.len());
+        for (chord_index, chord_input) in chords_input.iter().enumerate() {
+            let voicings = Self::voicings(chord_input);
+            if voicings.is_empty() {
+                return Err(SolveError::NoVoicing {
+                    chord_index,
+                    bass: chord_input.bass,
+                });
             }
+            all_voicings.push(voicings);
+        }
+        Ok(all_voicings)
+    }
 
-            prev_chord = Some(*chord);
+    // every chord that `generate_chord` could possibly return
+    pub fn voicings(chord_input: &ChordInput) -> Vec<Chord> {
+        let mut chords = Vec::new();
+        let b = chord_input.bass;
+        let possible_notes = chord_input.possible_notes();
+        for &t in possible_notes.iter() {
+            let t = Self::note_above(&b, t);
+            for &a in possible_notes.iter() {
+                let a = Self::note_above(&t, a);
+                for &s in possible_notes.iter() {
+                    let s = Self::note_above(&a, s);
+                    let chord = Chord::new(s, a, t, b);
+                    if Self::is_valid_chord(&chord, chord_input) {
+                        chords.push(chord);
+                    }
+                }
+            }
         }
-        score
+        chords
     }
 
-    pub fn new(chords_input: &[(NoteWithOctave, Vec<Note>)]) -> Self {
+    pub fn new(rng: &mut impl Rng, chords_input: &[ChordInput], scorer: &Scorer) -> Self {
         let mut chords: Vec<Chord> = Vec::with_capacity(chords_input.len());
 
         for chord_input in chords_input.iter() {
-            let chord = Self::generate_chord(chord_input);
+            let chord = Self::generate_chord(rng, chord_input);
             chords.push(chord);
         }
-        let score = Self::score(&chords);
+        let score = scorer.score(chords_input, &chords);
 
         Self { chords, score }
     }
     // randomly changes the realisation
-    pub fn mutate(&self, chords_input: &[(NoteWithOctave, Vec<Note>)]) -> Self {
+    pub fn mutate(&self, rng: &mut impl Rng, chords_input: &[ChordInput], scorer: &Scorer) -> Self {
         let mut realisation = self.clone();
-        // generate a new chord 3 times
+        // generate a new chord 3 times (or for every chord, if there are fewer than 3)
         let mut rand_indexes = Vec::with_capacity(3);
-        for _ in 0..3 {
+        for _ in 0..realisation.chords.len().min(3) {
             loop {
-                let rand_index = rand::thread_rng().gen_range(0..realisation.chords.len());
+                let rand_index = rng.gen_range(0..realisation.chords.len());
                 if !rand_indexes.contains(&rand_index) {
                     rand_indexes.push(rand_index);
                     break;
@@ -166,10 +285,10 @@ impl Realisation {
             }
         }
         for i in rand_indexes {
-            realisation.chords[i] = Self::generate_chord(&chords_input[i])
+            realisation.chords[i] = Self::generate_chord(rng, &chords_input[i])
         }
         // re-score now that we've changed stuff
-        realisation.score = Self::score(&realisation.chords);
+        realisation.score = scorer.score(chords_input, &realisation.chords);
 
         realisation
     }
@@ -181,25 +300,24 @@ pub struct Generation {
 }
 impl Generation {
     fn new(
-        population_size: u32,
-        non_mutated_percentage: f32,
-        mutate_thrice_percentage: f32,
-        mutate_twice_percentage: f32,
-        chords_input: &[(NoteWithOctave, Vec<Note>)],
+        rng: &mut impl Rng,
+        ml: &MachineLearning,
+        chords_input: &[ChordInput],
         prev_generation: &Option<Generation>,
     ) -> Self {
+        let population_size = ml.population_size;
+        let scorer = &ml.scorer;
         let mut realisations = Vec::with_capacity(population_size as usize);
 
         if let Some(prev_generation) = prev_generation {
             // sort the previous generation by their score
             let mut prev_generation = prev_generation.clone();
-            prev_generation
-                .realisations
-                .sort_by(|a, b| a.score.cmp(&b.score));
+            prev_generation.realisations.sort_by_key(|x| x.score);
 
-            let mutate_thrice_number = (population_size as f32 * mutate_thrice_percentage) as u32;
-            let mutate_twice_number = (population_size as f32 * mutate_twice_percentage) as u32;
-            let non_mutated_number = (population_size as f32 * non_mutated_percentage) as u32;
+            let mutate_thrice_number =
+                (population_size as f32 * ml.mutate_thrice_percentage) as u32;
+            let mutate_twice_number = (population_size as f32 * ml.mutate_twice_percentage) as u32;
+            let non_mutated_number = (population_size as f32 * ml.non_mutated_percentage) as u32;
 
             assert!(
                 (mutate_thrice_number * 3 + mutate_twice_number * 2 + non_mutated_number)
@@ -215,7 +333,7 @@ impl Generation {
             for _ in 0..mutate_thrice_number {
                 let realisation = prev_generation.realisations.pop().unwrap();
                 for _ in 0..3 {
-                    let mutated = realisation.mutate(chords_input);
+                    let mutated = realisation.mutate(rng, chords_input, scorer);
                     realisations.push(mutated);
                 }
             }
@@ -223,7 +341,7 @@ impl Generation {
             for _ in 0..mutate_twice_number {
                 let realisation = prev_generation.realisations.pop().unwrap();
                 for _ in 0..2 {
-                    let mutated = realisation.mutate(chords_input);
+                    let mutated = realisation.mutate(rng, chords_input, scorer);
                     realisations.push(mutated);
                 }
             }
@@ -232,13 +350,13 @@ impl Generation {
                 population_size - (mutate_thrice_number * 3 + mutate_twice_number * 2);
 
             for _ in 0..remaining_size {
-                let realisation = Realisation::new(chords_input);
+                let realisation = Realisation::new(rng, chords_input, scorer);
                 realisations.push(realisation);
             }
         } else {
             // if there is no previous generation, then just create new realisations
             for _ in 0..population_size {
-                let realisation = Realisation::new(chords_input);
+                let realisation = Realisation::new(rng, chords_input, scorer);
                 realisations.push(realisation);
             }
         }
@@ -248,6 +366,12 @@ impl Generation {
 }
 
 pub struct MachineLearning {
+    // whether to print the best score of each generation to stderr
+    pub print_progress: bool,
+    // the seed for the random number generator used by `start`, so that runs can be repeated
+    pub seed: Option<u64>,
+    pub scorer: Scorer,
+
     total_generations: u32,
     population_size: u32,
 
@@ -266,6 +390,10 @@ impl MachineLearning {
         mutate_twice_percentage: f32,
     ) -> Self {
         Self {
+            print_progress: true,
+            seed: None,
+            scorer: Scorer::default(),
+
             total_generations,
             population_size,
 
@@ -276,16 +404,55 @@ impl MachineLearning {
             current_generation: None,
         }
     }
-    pub fn start(&mut self, chords_input: &[(NoteWithOctave, Vec<Note>)]) -> Realisation {
+    fn check_options(&self) -> Result<(), SolveError> {
+        if self.total_generations == 0 {
+            return Err(SolveError::InvalidOptions(
+                "there must be at least 1 generation",
+            ));
+        }
+        if self.population_size == 0 {
+            return Err(SolveError::InvalidOptions(
+                "the population must be at least 1",
+            ));
+        }
+        // the realisations carried over from each generation have to fit in the next one
+        let percentages = [
+            self.non_mutated_percentage,
+            self.mutate_thrice_percentage,
+            self.mutate_twice_percentage,
+        ];
+        if percentages.iter().any(|x| !(0.0..=1.0).contains(x))
+            || self.non_mutated_percentage
+                + self.mutate_thrice_percentage * 3.0
+                + self.mutate_twice_percentage * 2.0
+                >= 1.0
+        {
+            return Err(SolveError::InvalidOptions(
+                "the realisations carried over from each generation must be less than the population",
+            ));
+        }
+        Ok(())
+    }
+    pub fn start(&mut self, chords_input: &[ChordInput]) -> Result<Realisation, SolveError> {
+        let mut rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        self.start_with_rng(&mut rng, chords_input)
+    }
+    // runs the algorithm with every random decision taken from `rng`
+    pub fn start_with_rng(
+        &mut self,
+        rng: &mut impl Rng,
+        chords_input: &[ChordInput],
+    ) -> Result<Realisation, SolveError> {
+        self.check_options()?;
+        // make sure every chord can be voiced, otherwise generating chords would never finish
+        Realisation::all_voicings(chords_input)?;
+
         for i in 0..self.total_generations {
-            let current_generation = Generation::new(
-                self.population_size,
-                self.non_mutated_percentage,
-                self.mutate_thrice_percentage,
-                self.mutate_twice_percentage,
-                chords_input,
-                &self.current_generation,
-            );
+            let current_generation =
+                Generation::new(rng, self, chords_input, &self.current_generation);
             self.current_generation = Some(current_generation);
 
             let generation = self.current_generation.clone().unwrap();
@@ -295,10 +462,12 @@ impl MachineLearning {
                     best_index = i;
                 }
             }
-            println!(
-                "current generation: {i}, best score: {}",
-                generation.realisations[best_index].score
-            );
+            if self.print_progress {
+                eprintln!(
+                    "current generation: {i}, best score: {}",
+                    generation.realisations[best_index].score
+                );
+            }
         }
         let generation = self.current_generation.clone().unwrap();
         let mut best_index = 0;
@@ -307,6 +476,60 @@ impl MachineLearning {
                 best_index = i;
             }
         }
-        generation.realisations[best_index].clone()
+        Ok(generation.realisations[best_index].clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the notes of the chords of a melodic minor exercise, which depend on where the bass goes
+    fn chord_notes(exercise: &str) -> Vec<String> {
+        let (key_sig, figures) = parse_exercise(exercise).unwrap();
+        ChordInput::from_figures(&figures, key_sig, MinorScale::Melodic)
+            .iter()
+            .map(|input| {
+                let notes: Vec<String> = input.notes.iter().map(|x| x.to_string()).collect();
+                notes.join(" ")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn the_bass_line_decides_the_form_of_the_melodic_minor() {
+        // rising to the next different bass note, or falling by step
+        assert_eq!(
+            chord_notes("key=A minor | D3 | D3 | E3 | D3 | C3"),
+            ["D F# A", "D F# A", "E G B", "D F A", "C E G"]
+        );
+        // a leap down counts as rising
+        assert_eq!(chord_notes("key=A minor | D3 | A2"), ["D F# A", "A C E"]);
+        // the last chord goes by the one before
+        assert_eq!(chord_notes("key=A minor | C3 | D3"), ["C E G#", "D F# A"]);
+        assert_eq!(chord_notes("key=A minor | E3 | D3"), ["E G B", "D F A"]);
+    }
+
+    #[test]
+    fn upper_voices_can_have_either_form() {
+        let (key_sig, figures) = parse_exercise("key=A minor | D3 | E3").unwrap();
+        let input = &ChordInput::from_figures(&figures, key_sig, MinorScale::Melodic)[0];
+        let f = Note::new(NoteName::F, None);
+        let f_sharp = Note::new(NoteName::F, Some(Accidental::Sharp));
+        assert_eq!(input.either_form, [(f, f_sharp)]);
+        assert_eq!(input.forms(f), [f, f_sharp]);
+        assert_eq!(input.forms(f_sharp), [f, f_sharp]);
+        assert!(input.altered_notes.contains(&f_sharp));
+
+        let d = |octave| NoteWithOctave::new(NoteName::D, None, octave);
+        let a = NoteWithOctave::new(NoteName::A, None, 3);
+        let chord = |s, a| Chord::new(s, a, NoteWithOctave::new(NoteName::A, None, 3), d(3));
+        let f4 = NoteWithOctave::from_note(f, 4);
+        let f_sharp4 = NoteWithOctave::from_note(f_sharp, 4);
+        assert!(Realisation::is_valid_chord(&chord(f4, d(4)), input));
+        assert!(Realisation::is_valid_chord(&chord(f_sharp4, d(4)), input));
+        // but not both in the same chord, or neither
+        assert!(!Realisation::is_valid_chord(&chord(f_sharp4, f4), input));
+        assert!(!Realisation::is_valid_chord(&chord(d(4), a), input));
     }
 }
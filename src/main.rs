@@ -1,96 +1,309 @@
-mod machine_learning;
-mod music_theory;
+use figured_bass::lilypond::to_lilypond;
+use figured_bass::midi::{to_midi, MidiOptions};
+use figured_bass::musicxml::{from_musicxml, to_musicxml};
+use figured_bass::{
+    compare_solvers, parse_exercise, report, solve, MinorScale, Realisation, Solver, SolverOptions,
+};
+use std::process::ExitCode;
 
-use machine_learning::*;
-use music_theory::{Accidental::*, NoteName::*, *};
+const USAGE: &str = "usage: figured-bass solve <exercise> [options]
 
-fn print_figured_bass(realisation: Realisation) {
-    println!();
-    println!();
-    println!();
+Realises a figured bass exercise, read from a text or MusicXML file (or - for stdin).
 
-    for chord in realisation.chords.iter() {
-        print!("{}   ", chord.s);
-        if chord.s.to_string().len() == 2 {
-            print!(" ");
+options:
+  --solver <solver>    genetic (the default), exact, or compare to run both and print how far
+                       the genetic algorithm is from the best possible score
+  --generations <n>    number of generations to run (default 100)
+  --population <n>     number of realisations in each generation (default 2000)
+  --seed <n>           seed for the random number generator
+  --minor <scale>      the form of the minor scale to take unfigured sixths and sevenths from in
+                       minor keys: natural (the default), harmonic, or melodic to raise them
+                       when the bass is rising
+  --format <format>    output format: text, musicxml, midi or lilypond
+                       (default: from the output file's extension, otherwise text)
+  -o, --output <file>  write the result to a file instead of stdout
+  --report             list every penalty and bonus that makes up the score (under the text
+                       output, or on stderr for the other formats)
+  -h, --help           print this message
+
+exit codes:
+  1  the input couldn't be read or the output couldn't be written
+  2  invalid command-line arguments
+  3  the exercise couldn't be parsed
+  4  the exercise couldn't be realised";
+
+#[derive(Clone, Copy, PartialEq)]
+enum InputFormat {
+    Text,
+    MusicXml,
+}
+impl InputFormat {
+    fn detect(path: &str, input: &str) -> Self {
+        let path = path.to_lowercase();
+        if path.ends_with(".musicxml") || path.ends_with(".xml") {
+            InputFormat::MusicXml
+        } else if path.ends_with(".txt") {
+            InputFormat::Text
+        } else if input.trim_start().starts_with('<') {
+            InputFormat::MusicXml
+        } else {
+            InputFormat::Text
         }
     }
-    println!();
-    for chord in realisation.chords.iter() {
-        print!("{}   ", chord.a);
-        if chord.a.to_string().len() == 2 {
-            print!(" ");
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum OutputFormat {
+    Text,
+    MusicXml,
+    Midi,
+    LilyPond,
+}
+impl OutputFormat {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "text" | "txt" => Some(OutputFormat::Text),
+            "musicxml" | "xml" => Some(OutputFormat::MusicXml),
+            "midi" | "mid" => Some(OutputFormat::Midi),
+            "lilypond" | "ly" => Some(OutputFormat::LilyPond),
+            _ => None,
         }
     }
-    println!();
-    for chord in realisation.chords.iter() {
-        print!("{}   ", chord.t);
-        if chord.t.to_string().len() == 2 {
-            print!(" ");
+    fn from_path(path: &str) -> Option<Self> {
+        let extension = path.rsplit_once('.')?.1.to_lowercase();
+        match extension.as_str() {
+            "musicxml" => Some(OutputFormat::MusicXml),
+            "midi" => Some(OutputFormat::Midi),
+            extension => Self::from_name(extension),
         }
     }
-    println!();
-    for chord in realisation.chords.iter() {
-        print!("{}   ", chord.b);
-        if chord.b.to_string().len() == 2 {
-            print!(" ");
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum SolverChoice {
+    Solver(Solver),
+    Compare,
+}
+
+struct SolveArgs {
+    solver: SolverChoice,
+    input: String,
+    output: Option<String>,
+    format: OutputFormat,
+    generations: u32,
+    population: u32,
+    seed: Option<u64>,
+    minor_scale: MinorScale,
+    report: bool,
+}
+impl SolveArgs {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut solver = SolverChoice::Solver(Solver::Genetic);
+        let mut input = None;
+        let mut output = None;
+        let mut format = None;
+        let mut generations = 100;
+        let mut population = 2000;
+        let mut seed = None;
+        let mut minor_scale = MinorScale::Natural;
+        let mut report = false;
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| args.next().ok_or_else(|| format!("{name} needs a value"));
+            match arg.as_str() {
+                "--solver" => {
+                    solver = match value(arg)?.as_str() {
+                        "genetic" => SolverChoice::Solver(Solver::Genetic),
+                        "exact" => SolverChoice::Solver(Solver::Exact),
+                        "compare" => SolverChoice::Compare,
+                        name => return Err(format!("unknown solver `{name}`")),
+                    }
+                }
+                "--generations" => generations = parse_number(arg, value(arg)?)?,
+                "--population" => population = parse_number(arg, value(arg)?)?,
+                "--seed" => seed = Some(parse_number(arg, value(arg)?)?),
+                "--minor" => {
+                    minor_scale = match value(arg)?.as_str() {
+                        "natural" => MinorScale::Natural,
+                        "harmonic" => MinorScale::Harmonic,
+                        "melodic" => MinorScale::Melodic,
+                        name => return Err(format!("unknown minor scale `{name}`")),
+                    }
+                }
+                "--format" => {
+                    let name = value(arg)?;
+                    format = Some(
+                        OutputFormat::from_name(name)
+                            .ok_or_else(|| format!("unknown output format `{name}`"))?,
+                    );
+                }
+                "-o" | "--output" => output = Some(value(arg)?.clone()),
+                "--report" => report = true,
+                option if option.starts_with('-') && option != "-" => {
+                    return Err(format!("unknown option `{option}`"))
+                }
+                path if input.is_none() => input = Some(path.to_owned()),
+                extra => return Err(format!("unexpected argument `{extra}`")),
+            }
         }
+
+        let input = input.ok_or("missing exercise file")?;
+        if generations == 0 || population == 0 {
+            return Err("--generations and --population must be at least 1".to_owned());
+        }
+        let format = match (format, &output) {
+            (Some(format), _) => format,
+            (None, Some(path)) => OutputFormat::from_path(path)
+                .ok_or_else(|| format!("can't tell the output format of `{path}`, use --format"))?,
+            (None, None) => OutputFormat::Text,
+        };
+        Ok(Self {
+            solver,
+            input,
+            output,
+            format,
+            generations,
+            population,
+            seed,
+            minor_scale,
+            report,
+        })
     }
-    println!();
-    println!();
-    println!();
 }
 
-fn main() {
-    // let key_sig_1 = KeySignature::from_note(E, Some(Flat), Tonality::Major).unwrap();
-    // let figured_bass_1 = vec![
-    //     Figure::new(E, Some(Flat), 3, vec![(5, None), (3, None)]),
-    //     Figure::new(F, None, 3, vec![(6, None), (3, None)]),
-    //     Figure::new(G, None, 3, vec![(6, None), (3, None)]),
-    //     Figure::new(A, Some(Flat), 3, vec![(5, None), (3, None)]),
-    //     Figure::new(B, Some(Flat), 3, vec![(6, None), (4, None)]),
-    //     Figure::new(B, Some(Flat), 2, vec![(5, None), (3, None)]),
-    //     Figure::new(C, None, 3, vec![(5, None), (3, None)]),
-    //     Figure::new(G, None, 2, vec![(6, None), (4, None)]),
-    //     Figure::new(A, Some(Flat), 2, vec![(6, None), (3, None)]),
-    //     Figure::new(E, Some(Flat), 3, vec![(5, None), (3, None)]),
-    //     Figure::new(D, None, 3, vec![(6, None), (3, None)]),
-    //     Figure::new(C, None, 3, vec![(5, None), (3, None)]),
-    //     Figure::new(A, Some(Flat), 2, vec![(6, None), (3, None)]),
-    //     Figure::new(B, Some(Flat), 2, vec![(5, None), (3, None)]),
-    //     Figure::new(E, Some(Flat), 3, vec![(5, None), (3, None)]),
-    // ];
-
-    let key_sig_2 = KeySignature::from_note(G, None, Tonality::Major).unwrap();
-    let figured_bass_2 = vec![
-        Figure::new(E, None, 3, vec![(5, None), (3, None)]),
-        Figure::new(F, Some(Sharp), 3, vec![(6, Some(Sharp)), (3, None)]),
-        Figure::new(G, None, 3, vec![(6, None), (3, None)]),
-        Figure::new(D, Some(Sharp), 3, vec![(6, None), (3, None)]),
-        Figure::new(E, None, 3, vec![(5, None), (3, None)]),
-        Figure::new(B, None, 2, vec![(5, None), (3, Some(Sharp))]),
-        Figure::new(C, None, 3, vec![(5, None), (3, None)]),
-        Figure::new(C, None, 3, vec![(6, None), (3, None)]),
-        Figure::new(B, None, 2, vec![(5, None), (3, Some(Sharp))]),
-        Figure::new(A, None, 2, vec![(6, None), (3, None)]),
-        Figure::new(G, None, 2, vec![(6, None), (3, None)]),
-        Figure::new(A, None, 2, vec![(5, None), (3, None)]),
-        Figure::new(B, None, 2, vec![(6, None), (4, None)]),
-        Figure::new(B, None, 2, vec![(5, None), (3, Some(Sharp))]),
-        Figure::new(E, None, 3, vec![(5, None), (3, None)]),
-    ];
-
-    let mut chords_input = Vec::with_capacity(figured_bass_2.len());
-    for figure in figured_bass_2.iter() {
-        let mut notes = Vec::with_capacity(figure.figures.len());
-        for note in figure.to_notes(key_sig_2) {
-            notes.push(note);
+fn parse_number<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value `{value}` for {name}"))
+}
+
+// the realisation as four rows of note names, from the soprano down
+fn figured_bass_grid(realisation: &Realisation) -> String {
+    let mut grid = String::new();
+    for voice in 0..4 {
+        for chord in realisation.chords.iter() {
+            grid += &format!("{:<6}", chord.voices()[voice].to_string());
         }
-        chords_input.push((figure.bass, notes));
+        grid.push('\n');
     }
+    grid
+}
+
+fn run_solve(args: SolveArgs) -> ExitCode {
+    let input = if args.input == "-" {
+        std::io::read_to_string(std::io::stdin())
+    } else {
+        std::fs::read_to_string(&args.input)
+    };
+    let input = match input {
+        Ok(input) => input,
+        Err(err) => {
+            eprintln!("error: could not read {}: {err}", args.input);
+            return ExitCode::from(1);
+        }
+    };
+    let exercise = match InputFormat::detect(&args.input, &input) {
+        InputFormat::Text => parse_exercise(&input),
+        InputFormat::MusicXml => from_musicxml(&input),
+    };
+    let (key_sig, figured_bass) = match exercise {
+        Ok(exercise) => exercise,
+        Err(err) => {
+            eprintln!("error: {}: {err}", args.input);
+            return ExitCode::from(3);
+        }
+    };
 
-    let mut ml = MachineLearning::new(100, 2000, 0.1, 0.2, 0.1);
-    let realisation = ml.start(&chords_input);
+    let options = SolverOptions {
+        solver: match args.solver {
+            SolverChoice::Solver(solver) => solver,
+            SolverChoice::Compare => Solver::Genetic,
+        },
+        generations: args.generations,
+        population: args.population,
+        seed: args.seed,
+        minor_scale: args.minor_scale,
+        print_progress: true,
+        ..SolverOptions::default()
+    };
+    let solved = match args.solver {
+        SolverChoice::Solver(_) => solve(key_sig, &figured_bass, &options),
+        SolverChoice::Compare => {
+            compare_solvers(key_sig, &figured_bass, &options).map(|comparison| {
+                eprintln!("genetic algorithm score: {}", comparison.genetic.score);
+                eprintln!("best possible score: {}", comparison.exact.score);
+                eprintln!(
+                    "the genetic algorithm is {} points short of the best",
+                    comparison.gap()
+                );
+                comparison.exact
+            })
+        }
+    };
+    let realisation = match solved {
+        Ok(realisation) => realisation,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return ExitCode::from(4);
+        }
+    };
+
+    let report = args
+        .report
+        .then(|| report(key_sig, &figured_bass, &realisation, &options));
+    if let (Some(report), false) = (&report, args.format == OutputFormat::Text) {
+        eprintln!("{report}");
+    }
 
-    print_figured_bass(realisation);
+    let output = match args.format {
+        OutputFormat::Text => match &report {
+            Some(report) => format!("{}\n{report}\n", figured_bass_grid(&realisation)).into_bytes(),
+            None => figured_bass_grid(&realisation).into_bytes(),
+        },
+        OutputFormat::MusicXml => to_musicxml(&realisation, &figured_bass, key_sig).into_bytes(),
+        OutputFormat::Midi => match to_midi(&realisation, &MidiOptions::default()) {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                eprintln!("error: {err}");
+                return ExitCode::from(1);
+            }
+        },
+        OutputFormat::LilyPond => to_lilypond(&realisation, &figured_bass, key_sig).into_bytes(),
+    };
+    let written = match &args.output {
+        Some(path) => std::fs::write(path, output),
+        None => std::io::Write::write_all(&mut std::io::stdout(), &output),
+    };
+    if let Err(err) = written {
+        let destination = args.output.as_deref().unwrap_or("stdout");
+        eprintln!("error: could not write {destination}: {err}");
+        return ExitCode::from(1);
+    }
+    ExitCode::SUCCESS
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|x| x == "-h" || x == "--help") {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+    match args.first().map(String::as_str) {
+        Some("solve") => match SolveArgs::parse(&args[1..]) {
+            Ok(args) => run_solve(args),
+            Err(err) => {
+                eprintln!("error: {err}\n\n{USAGE}");
+                ExitCode::from(2)
+            }
+        },
+        Some(command) => {
+            eprintln!("error: unknown command `{command}`\n\n{USAGE}");
+            ExitCode::from(2)
+        }
+        None => {
+            eprintln!("{USAGE}");
+            ExitCode::from(2)
+        }
+    }
 }
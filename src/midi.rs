@@ -0,0 +1,132 @@
+use crate::machine_learning::Realisation;
+use crate::music_theory::*;
+use std::fmt::Display;
+
+const TICKS_PER_BEAT: u16 = 480;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MidiError {
+    // the tempo is stored as microseconds per beat in 3 bytes, which is only enough for 4 beats
+    // per minute or more
+    InvalidTempo(u32),
+    // midi notes only go up to G9
+    NoteOutOfRange {
+        chord_index: usize,
+        note: NoteWithOctave,
+    },
+}
+impl Display for MidiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MidiError::InvalidTempo(tempo) => write!(
+                f,
+                "a tempo of {tempo} beats per minute can't be written to a midi file, it has to be at least 4"
+            ),
+            MidiError::NoteOutOfRange { chord_index, note } => write!(
+                f,
+                "chord {}: {note} is too high for a midi file",
+                chord_index + 1
+            ),
+        }
+    }
+}
+impl std::error::Error for MidiError {}
+
+pub struct MidiOptions {
+    // beats per minute
+    pub tempo: u32,
+    // how long each chord lasts, in ticks (there are 480 ticks in a beat)
+    pub note_length: u32,
+    // the general midi program for the soprano, alto, tenor and bass tracks
+    pub programs: [u8; 4],
+}
+impl Default for MidiOptions {
+    fn default() -> Self {
+        Self {
+            tempo: 80,
+            note_length: TICKS_PER_BEAT as u32,
+            // choir aahs
+            programs: [52; 4],
+        }
+    }
+}
+
+fn push_variable_length(bytes: &mut Vec<u8>, value: u32) {
+    // 7 bits per byte, most significant first, with the top bit set on all but the last byte
+    let mut groups = vec![(value & 0x7f) as u8];
+    let mut value = value >> 7;
+    while value > 0 {
+        groups.push((value & 0x7f) as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.extend(groups.iter().rev());
+}
+
+fn push_chunk(bytes: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) {
+    bytes.extend_from_slice(chunk_type);
+    bytes.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(data);
+}
+
+fn push_track_name(track: &mut Vec<u8>, name: &str) {
+    track.extend_from_slice(&[0x00, 0xff, 0x03]);
+    push_variable_length(track, name.len() as u32);
+    track.extend_from_slice(name.as_bytes());
+}
+
+fn push_end_of_track(track: &mut Vec<u8>) {
+    track.extend_from_slice(&[0x00, 0xff, 0x2f, 0x00]);
+}
+
+// middle C (C4) is midi note 60, so midi can't go above G9
+fn midi_pitch(note: NoteWithOctave) -> Option<u8> {
+    u8::try_from(note.to_semitones() + 12)
+        .ok()
+        .filter(|x| *x <= 127)
+}
+
+// writes the realisation as a type 1 standard midi file, with a tempo track followed by one
+// track (and channel) per voice
+pub fn to_midi(realisation: &Realisation, options: &MidiOptions) -> Result<Vec<u8>, MidiError> {
+    let microseconds_per_beat = match 60_000_000u32.checked_div(options.tempo) {
+        Some(microseconds) if microseconds <= 0xff_ffff => microseconds,
+        _ => return Err(MidiError::InvalidTempo(options.tempo)),
+    };
+    let mut bytes = Vec::new();
+
+    let mut header = Vec::with_capacity(6);
+    header.extend_from_slice(&1u16.to_be_bytes());
+    header.extend_from_slice(&5u16.to_be_bytes());
+    header.extend_from_slice(&TICKS_PER_BEAT.to_be_bytes());
+    push_chunk(&mut bytes, b"MThd", &header);
+
+    let mut tempo_track = Vec::new();
+    push_track_name(&mut tempo_track, "Figured Bass");
+    tempo_track.extend_from_slice(&[0x00, 0xff, 0x51, 0x03]);
+    tempo_track.extend_from_slice(&microseconds_per_beat.to_be_bytes()[1..]);
+    push_end_of_track(&mut tempo_track);
+    push_chunk(&mut bytes, b"MTrk", &tempo_track);
+
+    let names = ["Soprano", "Alto", "Tenor", "Bass"];
+    for (channel, name) in names.iter().enumerate() {
+        let channel = channel as u8;
+        let mut track = Vec::new();
+        push_track_name(&mut track, name);
+        track.extend_from_slice(&[
+            0x00,
+            0xc0 | channel,
+            options.programs[channel as usize] & 0x7f,
+        ]);
+        for (chord_index, chord) in realisation.chords.iter().enumerate() {
+            let note = chord.voices()[channel as usize];
+            let pitch = midi_pitch(note).ok_or(MidiError::NoteOutOfRange { chord_index, note })?;
+            track.extend_from_slice(&[0x00, 0x90 | channel, pitch, 80]);
+            push_variable_length(&mut track, options.note_length);
+            track.extend_from_slice(&[0x80 | channel, pitch, 0]);
+        }
+        push_end_of_track(&mut track);
+        push_chunk(&mut bytes, b"MTrk", &track);
+    }
+
+    Ok(bytes)
+}
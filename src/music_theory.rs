@@ -2,7 +2,6 @@ use std::fmt::Display;
 
 // const MAJOR_SCALE: [u32; 6] = [2, 2, 1, 2, 2, 2];
 const MAJOR_SCALE: [u32; 7] = [0, 2, 4, 5, 7, 9, 11];
-const MINOR_SCALE: [u32; 7] = [0, 2, 3, 5, 7, 8, 10];
 
 #[derive(PartialEq, Clone, Copy, Debug)]
 pub enum NoteName {
@@ -15,6 +14,18 @@ pub enum NoteName {
     B,
 }
 impl NoteName {
+    // how many letters the note is above C
+    fn letter_number(self) -> i32 {
+        match self {
+            Self::C => 0,
+            Self::D => 1,
+            Self::E => 2,
+            Self::F => 3,
+            Self::G => 4,
+            Self::A => 5,
+            Self::B => 6,
+        }
+    }
     fn get_semitone(self) -> i32 {
         match self {
             Self::C => 0,
@@ -97,16 +108,36 @@ impl TryFrom<u32> for NoteName {
     }
 }
 
+// each accidental is the number of semitones it moves a note by
 #[derive(PartialEq, Clone, Copy, Debug)]
 pub enum Accidental {
+    DoubleFlat = -2,
     Flat = -1,
+    Natural = 0,
     Sharp = 1,
+    DoubleSharp = 2,
+}
+impl Accidental {
+    // the accidental that moves a note by this many semitones, if there is one
+    pub fn from_alteration(semitones: i32) -> Option<Self> {
+        match semitones {
+            -2 => Some(Accidental::DoubleFlat),
+            -1 => Some(Accidental::Flat),
+            0 => Some(Accidental::Natural),
+            1 => Some(Accidental::Sharp),
+            2 => Some(Accidental::DoubleSharp),
+            _ => None,
+        }
+    }
 }
 impl Display for Accidental {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let result = match self {
+            Accidental::DoubleFlat => "bb",
             Accidental::Flat => "b",
+            Accidental::Natural => "n",
             Accidental::Sharp => "#",
+            Accidental::DoubleSharp => "##",
         };
         write!(f, "{}", result)
     }
@@ -118,50 +149,71 @@ pub struct Note {
     pub accidental: Option<Accidental>,
 }
 impl Note {
+    // the note is kept spelt the way it is given, so E# stays E# rather than becoming F
     pub fn new(name: NoteName, accidental: Option<Accidental>) -> Self {
-        let note = Self { name, accidental };
-        // if the enharmonic equivalent is better, use that instead
-        let enharmonic_equivalent = note.enharmonic_equivalent();
-        if enharmonic_equivalent.accidental.is_none() {
-            enharmonic_equivalent
-        } else {
-            note
-        }
+        // a natural sign on a note is the same as no accidental at all
+        let accidental = accidental.filter(|x| *x != Accidental::Natural);
+        Self { name, accidental }
+    }
+    // the number of semitones from the C with the same octave number, which is -1 for Cb and 12
+    // for B#
+    fn semitones_from_c(self) -> i32 {
+        self.name.get_semitone() + self.accidental.map_or(0, |x| x as i32)
     }
+    // the number of semitones above C, between 0 and 11
     pub fn to_semitones(self) -> u32 {
-        let mut semitones: i32 = 0;
-        semitones += self.name.get_semitone();
-        if let Some(accidental) = self.accidental {
-            semitones += accidental as i32;
-        }
-        semitones as u32
+        self.semitones_from_c().rem_euclid(12) as u32
     }
+    // the note this many semitones above C, spelt with sharps or flats depending on `accidental`
     pub fn from_semitones(semitones: u32, accidental: Accidental) -> Self {
         let semitones = semitones % 12;
         let (name, accidental) = match NoteName::from_semitones(semitones) {
             Some(name) => (name, None),
-            None => match accidental {
-                Accidental::Sharp => (
-                    NoteName::from_semitones(semitones - 1).unwrap(),
-                    Some(Accidental::Sharp),
-                ),
-                Accidental::Flat => (
-                    NoteName::from_semitones(semitones + 1).unwrap(),
-                    Some(Accidental::Flat),
-                ),
-            },
+            None if (accidental as i32) < 0 => (
+                NoteName::from_semitones(semitones + 1).unwrap(),
+                Some(Accidental::Flat),
+            ),
+            None => (
+                NoteName::from_semitones(semitones - 1).unwrap(),
+                Some(Accidental::Sharp),
+            ),
         };
         Self { name, accidental }
     }
+    // the note with this letter that is the given number of semitones above C (give or take
+    // octaves), e.g. E and 5 semitones gives E#
+    fn with_letter(name: NoteName, semitones: i32) -> Self {
+        let natural = Note::new(name, None);
+        // how far the natural note is from the one we want, between -6 and 5
+        let alteration = (semitones - natural.semitones_from_c() + 6).rem_euclid(12) - 6;
+        natural.altered(alteration)
+    }
+    // the note raised or lowered by this many semitones, keeping the same letter where it can,
+    // e.g. raising Bb by 1 gives B and raising F# by 1 gives F##
+    pub fn altered(self, semitones: i32) -> Self {
+        let alteration = self.accidental.map_or(0, |x| x as i32) + semitones;
+        match Accidental::from_alteration(alteration) {
+            Some(accidental) => Self::new(self.name, Some(accidental)),
+            // there's no accidental for more than two semitones, so it has to be respelt
+            None => Self::from_semitones(
+                (self.semitones_from_c() + semitones).rem_euclid(12) as u32,
+                if semitones > 0 {
+                    Accidental::Sharp
+                } else {
+                    Accidental::Flat
+                },
+            ),
+        }
+    }
     pub fn semitones_between(&self, other: Note) -> u32 {
         ((self.to_semitones() as i32) - (other.to_semitones() as i32)).unsigned_abs()
     }
     pub fn enharmonic_equivalent(self) -> Self {
         match self.accidental {
-            Some(accidental) => match accidental {
-                Accidental::Sharp => Note::from_semitones(self.to_semitones(), Accidental::Flat),
-                Accidental::Flat => Note::from_semitones(self.to_semitones(), Accidental::Sharp),
-            },
+            Some(Accidental::Sharp | Accidental::DoubleSharp) => {
+                Note::from_semitones(self.to_semitones(), Accidental::Flat)
+            }
+            Some(_) => Note::from_semitones(self.to_semitones(), Accidental::Sharp),
             None => self,
         }
     }
@@ -187,8 +239,13 @@ impl NoteWithOctave {
         let note = Note::new(name, accidental);
         Self { note, octave }
     }
+    // the number of semitones above C0, which the parsers make sure no note is below
     pub fn to_semitones(self) -> u32 {
-        self.note.to_semitones() + (self.octave * 12)
+        (self.note.semitones_from_c() + (self.octave as i32 * 12)) as u32
+    }
+    // whether the note is below C0, e.g. Cb0, which can't be given as a number of semitones
+    pub fn is_below_c0(self) -> bool {
+        self.note.semitones_from_c() + (self.octave as i32 * 12) < 0
     }
     pub fn from_semitones(semitones: u32, accidental: Accidental) -> Self {
         let note = Note::from_semitones(semitones, accidental);
@@ -204,6 +261,11 @@ impl NoteWithOctave {
     pub fn semitones_up_to(&self, other: NoteWithOctave) -> i32 {
         (other.to_semitones() as i32) - (self.to_semitones() as i32)
     }
+    // how many letter names up the other note is, so C4 to D#4 is 1 step and C4 to Eb4 is 2
+    pub fn steps_up_to(&self, other: NoteWithOctave) -> i32 {
+        (other.note.name.letter_number() + other.octave as i32 * 7)
+            - (self.note.name.letter_number() + self.octave as i32 * 7)
+    }
     pub fn in_range_inclusive(&self, lb: NoteWithOctave, ub: NoteWithOctave) -> bool {
         lb.semitones_up_to(*self) >= 0 && self.semitones_up_to(ub) >= 0
     }
@@ -213,129 +275,336 @@ impl Display for NoteWithOctave {
         write!(f, "{}{}", self.note, self.octave)
     }
 }
+impl std::ops::Add<Interval> for NoteWithOctave {
+    type Output = NoteWithOctave;
+    // the note the interval above, e.g. a major third above D4 is F#4
+    fn add(self, interval: Interval) -> NoteWithOctave {
+        let steps = self.note.name.letter_number() + self.octave as i32 * 7;
+        self.note_at(steps + interval.steps(), interval.semitones())
+    }
+}
+impl std::ops::Sub<Interval> for NoteWithOctave {
+    type Output = NoteWithOctave;
+    // the note the interval below, e.g. a minor third below D4 is B3
+    fn sub(self, interval: Interval) -> NoteWithOctave {
+        let steps = self.note.name.letter_number() + self.octave as i32 * 7;
+        self.note_at(steps - interval.steps(), -interval.semitones())
+    }
+}
+impl NoteWithOctave {
+    // the note with the letter and octave that is `steps` letters above C0, spelt so that it is
+    // `semitones` away from this note
+    fn note_at(self, steps: i32, semitones: i32) -> NoteWithOctave {
+        let name = NoteName::try_from(steps.rem_euclid(7) as u32).unwrap();
+        let natural = NoteWithOctave::new(name, None, steps.div_euclid(7) as u32);
+        let alteration = self.to_semitones() as i32 + semitones - natural.to_semitones() as i32;
+        match Accidental::from_alteration(alteration) {
+            Some(accidental) => NoteWithOctave::new(name, Some(accidental), natural.octave),
+            // there's no accidental for more than two semitones, so it has to be respelt
+            None => NoteWithOctave::from_semitones(
+                (self.to_semitones() as i32 + semitones) as u32,
+                if alteration > 0 {
+                    Accidental::Sharp
+                } else {
+                    Accidental::Flat
+                },
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Quality {
+    // how many times diminished, so 2 is doubly diminished
+    Diminished(u32),
+    Minor,
+    Perfect,
+    Major,
+    Augmented(u32),
+}
 
+// the distance between two spelt notes, e.g. a major third or a diminished fourth (which are
+// both 4 semitones)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval {
+    pub quality: Quality,
+    // the number within an octave, from 1 for a unison up to 7 for a seventh
+    pub number: u32,
+    // how many octaves it spans on top of that, so a tenth is a third with 1 octave
+    pub octaves: u32,
+}
+impl Interval {
+    // None if the quality doesn't go with the number, like a major fifth or a perfect third
+    pub fn new(quality: Quality, number: u32, octaves: u32) -> Option<Self> {
+        if !(1..=7).contains(&number) {
+            return None;
+        }
+        let is_perfect = matches!(number, 1 | 4 | 5);
+        match quality {
+            Quality::Perfect if !is_perfect => None,
+            Quality::Major | Quality::Minor if is_perfect => None,
+            Quality::Diminished(0) | Quality::Augmented(0) => None,
+            _ => Some(Self {
+                quality,
+                number,
+                octaves,
+            }),
+        }
+    }
+    // the interval from the lower of the two notes up to the higher one, going by letters rather
+    // than sound, so C4 to Dbb4 is a diminished second even though it sounds lower
+    pub fn between(a: NoteWithOctave, b: NoteWithOctave) -> Self {
+        let (steps, semitones) = match (a.steps_up_to(b), a.semitones_up_to(b)) {
+            (steps, semitones) if steps < 0 || (steps == 0 && semitones < 0) => {
+                (-steps, -semitones)
+            }
+            other => other,
+        };
+        let number = (steps % 7) as u32 + 1;
+        let difference = semitones - MAJOR_SCALE[(steps % 7) as usize] as i32 - 12 * (steps / 7);
+        let quality = match (matches!(number, 1 | 4 | 5), difference) {
+            (_, difference) if difference > 0 => Quality::Augmented(difference as u32),
+            (true, 0) => Quality::Perfect,
+            (true, difference) => Quality::Diminished(difference.unsigned_abs()),
+            (false, 0) => Quality::Major,
+            (false, -1) => Quality::Minor,
+            (false, difference) => Quality::Diminished(difference.unsigned_abs() - 1),
+        };
+        Self {
+            quality,
+            number,
+            octaves: (steps / 7) as u32,
+        }
+    }
+    // the number of letters spanned, not counting the first, so a third is 2 steps
+    fn steps(&self) -> i32 {
+        (self.number - 1 + self.octaves * 7) as i32
+    }
+    // the number counting the octaves, e.g. 10 for a tenth
+    pub fn compound_number(&self) -> u32 {
+        self.number + self.octaves * 7
+    }
+    pub fn semitones(&self) -> i32 {
+        let is_perfect = matches!(self.number, 1 | 4 | 5);
+        let offset = match self.quality {
+            Quality::Diminished(times) if is_perfect => -(times as i32),
+            Quality::Diminished(times) => -(times as i32) - 1,
+            Quality::Minor => -1,
+            Quality::Perfect | Quality::Major => 0,
+            Quality::Augmented(times) => times as i32,
+        };
+        MAJOR_SCALE[self.number as usize - 1] as i32 + self.octaves as i32 * 12 + offset
+    }
+    // the same interval within an octave, so a major tenth becomes a major third (an octave stays
+    // an octave rather than becoming a unison)
+    pub fn simple(&self) -> Self {
+        let octaves = if self.number == 1 && self.octaves > 0 {
+            1
+        } else {
+            0
+        };
+        Self { octaves, ..*self }
+    }
+    // the interval that adds up to an octave with the simple version of this one, e.g. a major
+    // third inverts to a minor sixth
+    pub fn inverted(&self) -> Self {
+        let simple = self.simple();
+        let quality = match simple.quality {
+            Quality::Diminished(times) => Quality::Augmented(times),
+            Quality::Minor => Quality::Major,
+            Quality::Perfect => Quality::Perfect,
+            Quality::Major => Quality::Minor,
+            Quality::Augmented(times) => Quality::Diminished(times),
+        };
+        let (number, octaves) = match (simple.number, simple.octaves) {
+            (1, 0) => (1, 1),
+            (1, _) => (1, 0),
+            (number, _) => (9 - number, 0),
+        };
+        Self {
+            quality,
+            number,
+            octaves,
+        }
+    }
+    pub fn is_augmented_or_diminished(&self) -> bool {
+        matches!(self.quality, Quality::Augmented(_) | Quality::Diminished(_))
+    }
+}
+impl Display for Interval {
+    // written the short way, e.g. M3 for a major third, d5 for a diminished fifth or P8 for an
+    // octave
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let quality = match self.quality {
+            Quality::Diminished(times) => "d".repeat(times as usize),
+            Quality::Minor => "m".to_owned(),
+            Quality::Perfect => "P".to_owned(),
+            Quality::Major => "M".to_owned(),
+            Quality::Augmented(times) => "A".repeat(times as usize),
+        };
+        write!(f, "{}{}", quality, self.compound_number())
+    }
+}
+
+// major and minor, along with the other church modes
 #[derive(PartialEq, Debug, Clone, Copy)]
 pub enum Tonality {
     Major,
     Minor,
+    Dorian,
+    Phrygian,
+    Lydian,
+    Mixolydian,
+    Locrian,
+}
+impl Tonality {
+    // which degree of the major scale with the same key signature the final is on, from 0 for
+    // the tonic of the major scale
+    fn degree(self) -> usize {
+        match self {
+            Tonality::Major => 0,
+            Tonality::Dorian => 1,
+            Tonality::Phrygian => 2,
+            Tonality::Lydian => 3,
+            Tonality::Mixolydian => 4,
+            Tonality::Minor => 5,
+            Tonality::Locrian => 6,
+        }
+    }
+}
+impl From<Tonality> for &str {
+    fn from(value: Tonality) -> Self {
+        match value {
+            Tonality::Major => "major",
+            Tonality::Minor => "minor",
+            Tonality::Dorian => "dorian",
+            Tonality::Phrygian => "phrygian",
+            Tonality::Lydian => "lydian",
+            Tonality::Mixolydian => "mixolydian",
+            Tonality::Locrian => "locrian",
+        }
+    }
+}
+impl TryFrom<&str> for Tonality {
+    type Error = ();
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_ascii_lowercase().as_str() {
+            // ionian and aeolian are the same as major and minor
+            "major" | "ionian" => Ok(Tonality::Major),
+            "minor" | "aeolian" => Ok(Tonality::Minor),
+            "dorian" => Ok(Tonality::Dorian),
+            "phrygian" => Ok(Tonality::Phrygian),
+            "lydian" => Ok(Tonality::Lydian),
+            "mixolydian" => Ok(Tonality::Mixolydian),
+            "locrian" => Ok(Tonality::Locrian),
+            _ => Err(()),
+        }
+    }
+}
+
+// which form of the minor scale gives the sixth and seventh degrees of a minor key when the
+// figures don't say
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub enum MinorScale {
+    // the notes of the key signature, so a raised leading tone has to be figured
+    #[default]
+    Natural,
+    // the seventh degree is always raised
+    Harmonic,
+    // the sixth and seventh degrees are raised when the line is rising, and left as they are in
+    // the key signature when it is falling
+    Melodic,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct KeySignature {
-    pub accidental: Accidental,
-    pub number: u32,
+    // the number of sharps, or minus the number of flats, which is always from -7 to 7
+    fifths: i32,
     pub tonality: Tonality,
 }
 impl KeySignature {
-    #[allow(clippy::manual_map)]
+    // the key signature with this many sharps, or flats if it is negative, from -7 (seven flats)
+    // to 7 (seven sharps)
+    pub fn from_fifths(fifths: i32, tonality: Tonality) -> Option<Self> {
+        if !(-7..=7).contains(&fifths) {
+            return None;
+        }
+        Some(Self { fifths, tonality })
+    }
+    // the number of sharps, or minus the number of flats
+    pub fn fifths(&self) -> i32 {
+        self.fifths
+    }
+    // the key with this tonic (or final, for the modes), if it has seven or fewer sharps or flats
     pub fn from_note(
         note_name: NoteName,
         accidental: Option<Accidental>,
         tonality: Tonality,
     ) -> Option<Self> {
         let note = Note::new(note_name, accidental);
-        let major_sharp_keys = vec![
-            Note::new(NoteName::C, None),
-            Note::new(NoteName::G, None),
-            Note::new(NoteName::D, None),
-            Note::new(NoteName::A, None),
-            Note::new(NoteName::E, None),
-            Note::new(NoteName::B, None),
-            Note::new(NoteName::F, Some(Accidental::Sharp)),
-        ];
-        let minor_sharp_keys = vec![
-            Note::new(NoteName::A, None),
-            Note::new(NoteName::E, None),
-            Note::new(NoteName::B, None),
-            Note::new(NoteName::F, Some(Accidental::Sharp)),
-            Note::new(NoteName::C, Some(Accidental::Sharp)),
-            Note::new(NoteName::G, Some(Accidental::Sharp)),
-            Note::new(NoteName::D, Some(Accidental::Sharp)),
-        ];
-        let major_flat_keys = vec![
-            Note::new(NoteName::C, None),
-            Note::new(NoteName::F, None),
-            Note::new(NoteName::B, Some(Accidental::Flat)),
-            Note::new(NoteName::E, Some(Accidental::Flat)),
-            Note::new(NoteName::A, Some(Accidental::Flat)),
-            Note::new(NoteName::D, Some(Accidental::Flat)),
-            Note::new(NoteName::G, Some(Accidental::Flat)),
-        ];
-        let minor_flat_keys = vec![
-            Note::new(NoteName::A, None),
-            Note::new(NoteName::D, None),
-            Note::new(NoteName::G, None),
-            Note::new(NoteName::C, None),
-            Note::new(NoteName::F, None),
-            Note::new(NoteName::B, Some(Accidental::Flat)),
-            Note::new(NoteName::E, Some(Accidental::Flat)),
-        ];
-        match tonality {
-            Tonality::Major => {
-                if let Some(pos) = major_sharp_keys.iter().position(|&x| x == note) {
-                    Some(Self {
-                        accidental: Accidental::Sharp,
-                        number: pos as u32,
-                        tonality: Tonality::Major,
-                    })
-                } else if let Some(pos) = major_flat_keys.iter().position(|&x| x == note) {
-                    Some(Self {
-                        accidental: Accidental::Flat,
-                        number: pos as u32,
-                        tonality: Tonality::Major,
-                    })
-                } else {
-                    None
-                }
-            }
-            Tonality::Minor => {
-                if let Some(pos) = minor_sharp_keys.iter().position(|&x| x == note) {
-                    Some(Self {
-                        accidental: Accidental::Sharp,
-                        number: pos as u32,
-                        tonality: Tonality::Minor,
-                    })
-                } else if let Some(pos) = minor_flat_keys.iter().position(|&x| x == note) {
-                    Some(Self {
-                        accidental: Accidental::Flat,
-                        number: pos as u32,
-                        tonality: Tonality::Minor,
-                    })
-                } else {
-                    return None;
-                }
-            }
-        }
+        (-7..=7)
+            .filter_map(|fifths| Self::from_fifths(fifths, tonality))
+            .find(|key_sig| key_sig.get_starting_note() == note)
     }
     pub fn get_starting_note(&self) -> Note {
-        let cycle_direction = if self.accidental == Accidental::Sharp {
-            7
-        } else {
-            5
-        };
-        match self.tonality {
-            Tonality::Major => Note::from_semitones(self.number * cycle_direction, self.accidental),
-            Tonality::Minor => {
-                Note::from_semitones(self.number * cycle_direction - 3, self.accidental)
-            }
-        }
+        // each sharp moves the major key up a fifth, which is 4 letters and 7 semitones, and each
+        // flat moves it down a fifth
+        let fifths = self.fifths();
+        let (letter, semitones) = (fifths * 4, fifths * 7);
+        // the other modes with the same signature start on another degree of the major scale, e.g.
+        // the minor key is a sixth (5 letters and 9 semitones) above
+        let degree = self.tonality.degree();
+        let (letter, semitones) = (
+            letter + degree as i32,
+            semitones + MAJOR_SCALE[degree] as i32,
+        );
+        Note::with_letter(
+            NoteName::try_from(letter.rem_euclid(7) as u32).unwrap(),
+            semitones,
+        )
     }
+    // the seventh degree of the scale raised to a semitone below the tonic, as it is in minor keys
+    // and most of the modes
+    pub fn leading_tone(&self) -> Note {
+        let scale = self.get_scale();
+        Note::with_letter(scale[6].name, scale[0].semitones_from_c() - 1)
+    }
+    // the notes of the scale from the tonic up, with one note for each letter, which are the
+    // notes of the major scale with the same key signature starting from another degree
     pub fn get_scale(&self) -> Vec<Note> {
-        let mut semitones = Vec::with_capacity(7);
+        let tonic = self.get_starting_note();
+        let degree = self.tonality.degree();
+        let mut notes = Vec::with_capacity(7);
         for i in 0..7 {
-            if self.tonality == Tonality::Major {
-                semitones.push(MAJOR_SCALE[i] + self.get_starting_note().to_semitones());
-            } else {
-                semitones.push(MINOR_SCALE[i] + self.get_starting_note().to_semitones());
+            let letter = (tonic.name.letter_number() as u32 + i as u32) % 7;
+            let semitones = MAJOR_SCALE[(degree + i) % 7] as i32 - MAJOR_SCALE[degree] as i32;
+            notes.push(Note::with_letter(
+                NoteName::try_from(letter).unwrap(),
+                tonic.semitones_from_c() + semitones,
+            ));
+        }
+        notes
+    }
+    // the scale with the sixth and seventh degrees of a minor key taken from `minor_scale`, for a
+    // line that is rising (or falling, if `ascending` is false). the other tonalities only have
+    // the one scale.
+    pub fn get_scale_for(&self, minor_scale: MinorScale, ascending: bool) -> Vec<Note> {
+        let mut scale = self.get_scale();
+        if self.tonality == Tonality::Minor {
+            let raised: &[usize] = match minor_scale {
+                MinorScale::Harmonic => &[6],
+                MinorScale::Melodic if ascending => &[5, 6],
+                MinorScale::Natural | MinorScale::Melodic => &[],
+            };
+            for i in raised {
+                scale[*i] = scale[*i].altered(1);
             }
         }
-        semitones
-            .iter()
-            .map(|x| Note::from_semitones(*x, self.accidental))
-            .collect()
+        scale
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Figure {
     pub bass: NoteWithOctave,
     pub figures: Vec<(u32, Option<Accidental>)>,
@@ -355,34 +624,144 @@ impl Figure {
             figures,
         }
     }
+    // the accidental, if the figure is only an accidental, which stands for an altered third and
+    // is written without the number
+    pub fn lone_accidental(&self) -> Option<Accidental> {
+        match self.figures.as_slice() {
+            [(3, accidental)] => *accidental,
+            _ => None,
+        }
+    }
+    // fills in the intervals that thorough-bass shorthand leaves implied,
+    // e.g. a blank figure means 5/3, `6` means 6/3 and `4/2` means 6/4/2
+    pub fn expanded_figures(&self) -> Vec<(u32, Option<Accidental>)> {
+        let numbers: Vec<u32> = self.figures.iter().map(|x| x.0).collect();
+        let implied: &[u32] = match numbers.as_slice() {
+            // a lone accidental is an altered third, so it is treated the same as a 3
+            [] | [3] | [5] => &[3, 5],
+            [6] => &[3],
+            [7] | [3, 7] | [5, 7] => &[3, 5],
+            [5, 6] => &[3],
+            [3, 4] => &[6],
+            [2] | [2, 4] => &[4, 6],
+            _ => &[],
+        };
+        let mut figures = self.figures.clone();
+        for number in implied {
+            if !numbers.contains(number) {
+                figures.push((*number, None));
+            }
+        }
+        figures.sort_by_key(|x| x.0);
+        figures
+    }
     pub fn to_notes(&self, key_sig: KeySignature) -> Vec<Note> {
+        self.to_notes_for(key_sig, MinorScale::Natural, true)
+    }
+    // the notes of the chord when the sixth and seventh degrees of a minor key come from
+    // `minor_scale`, for a bass that is rising (or falling, if `ascending` is false)
+    pub fn to_notes_for(
+        &self,
+        key_sig: KeySignature,
+        minor_scale: MinorScale,
+        ascending: bool,
+    ) -> Vec<Note> {
         let mut chord = vec![self.bass.note];
-        let mut scale = key_sig.get_scale();
-        // put the bass note at the front of the vector
+        let mut scale = key_sig.get_scale_for(minor_scale, ascending);
+        // accidentals in the figures are relative to the key signature, not the minor scale
+        let mut key_scale = key_sig.get_scale();
+        // put the bass note at the front of the vectors
         while scale[0].name != self.bass.note.name {
             scale.rotate_left(1);
+            key_scale.rotate_left(1);
         }
-        for figure in self.figures.iter() {
-            // go however many notes up the scale to get to the correct note
-            let mut note = scale[((figure.0 - 1) % 8) as usize];
-            // if the figure has an accidental, shift it up or down by 1
+        for figure in self.expanded_figures().iter() {
+            // go however many notes up the scale to get to the correct note. a figure is only the
+            // number of an interval, and its quality comes from the key, so it can't be made into
+            // an `Interval` until the note has been found this way
+            let position = ((figure.0 - 1) % 7) as usize;
+            let mut note = scale[position];
             if let Some(accidental) = figure.1 {
+                let note_in_key = key_scale[position];
                 note = match accidental {
-                    Accidental::Sharp => {
-                        Note::from_semitones(note.to_semitones() + 1, Accidental::Sharp)
-                    }
-                    Accidental::Flat => {
-                        Note::from_semitones(note.to_semitones() + 11, Accidental::Flat)
-                    }
+                    // a natural cancels whatever the key does to the note
+                    Accidental::Natural => Note::new(note.name, None),
+                    // the others raise or lower the note from where it is in the key, so a sharp
+                    // on a note that is flat in the key makes it natural
+                    accidental => note_in_key.altered(accidental as i32),
                 };
             }
             chord.push(note);
         }
         chord
     }
+    // the note that should be doubled in the chord: the root of a 5/3 chord or the bass of a 6/4
+    // chord, unless that is the leading tone
+    pub fn preferred_doubling(&self, key_sig: KeySignature) -> Option<Note> {
+        let numbers: Vec<u32> = self.expanded_figures().iter().map(|x| x.0).collect();
+        if !(numbers == [3, 5] || numbers == [4, 6]) {
+            return None;
+        }
+        if self.bass.note.to_semitones() == key_sig.leading_tone().to_semitones() {
+            return None;
+        }
+        Some(self.bass.note)
+    }
+    // the chromatic notes in the chord, given the notes from `to_notes`: the ones that have been
+    // raised or lowered by an accidental in the figures, and any others (the bass included) that
+    // aren't in the key's scale
+    pub fn altered_notes(&self, key_sig: KeySignature, notes: &[Note]) -> Vec<Note> {
+        let scale = key_sig.get_scale();
+        let mut altered: Vec<Note> = notes
+            .iter()
+            .filter(|x| !scale.contains(x))
+            .copied()
+            .collect();
+        for (figure, note) in self.expanded_figures().iter().zip(notes[1..].iter()) {
+            if figure.1.is_some() && !altered.contains(note) {
+                altered.push(*note);
+            }
+        }
+        altered
+    }
+    // the notes the figures raise above where they are in the key, given the notes from
+    // `to_notes`, which want to rise a semitone like a leading tone
+    pub fn raised_notes(&self, key_sig: KeySignature, notes: &[Note]) -> Vec<Note> {
+        let scale = key_sig.get_scale();
+        let mut raised = Vec::new();
+        for (figure, note) in self.expanded_figures().iter().zip(notes[1..].iter()) {
+            if figure.1.is_none() || raised.contains(note) {
+                continue;
+            }
+            let in_key = scale.iter().find(|x| x.name == note.name).unwrap();
+            if (1..=2).contains(&((note.to_semitones() + 12 - in_key.to_semitones()) % 12)) {
+                raised.push(*note);
+            }
+        }
+        raised
+    }
+    // the note that is the seventh of the chord, for chords figured 7, 6/5, 4/3 or 4/2, given the
+    // notes from `to_notes`
+    pub fn seventh(&self, notes: &[Note]) -> Option<Note> {
+        let numbers: Vec<u32> = self.expanded_figures().iter().map(|x| x.0).collect();
+        let seventh_figure = match numbers.as_slice() {
+            [3, 5, 7] => 7,
+            [3, 5, 6] => 5,
+            [3, 4, 6] => 3,
+            // in a 4/2 chord the seventh is in the bass
+            [2, 4, 6] => return Some(self.bass.note),
+            _ => return None,
+        };
+        let position = numbers.iter().position(|x| *x == seventh_figure)?;
+        // `to_notes` gives the bass first, then a note for each figure in order
+        Some(notes[position + 1])
+    }
 }
 
-#[derive(Clone, Copy, Debug)]
+// the exported scores give every chord a crotchet, in bars of 4/4
+pub const CHORDS_PER_MEASURE: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Chord {
     pub s: NoteWithOctave,
     pub a: NoteWithOctave,
@@ -393,4 +772,241 @@ impl Chord {
     pub fn new(s: NoteWithOctave, a: NoteWithOctave, t: NoteWithOctave, b: NoteWithOctave) -> Self {
         Self { s, a, t, b }
     }
+    // the notes from the top voice down
+    pub fn voices(&self) -> [NoteWithOctave; 4] {
+        [self.s, self.a, self.t, self.b]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+impl ParseError {
+    pub fn new(line: usize, column: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+}
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+impl std::error::Error for ParseError {}
+
+// splits a line into its `|`-separated segments, each a list of (column, word) pairs
+fn split_segments(line: &str) -> Vec<Vec<(usize, &str)>> {
+    let mut segments = vec![Vec::new()];
+    let mut word_start: Option<(usize, usize)> = None;
+    for (column, (index, c)) in line.char_indices().enumerate() {
+        if c.is_whitespace() || c == '|' {
+            if let Some((start_column, start_index)) = word_start.take() {
+                segments
+                    .last_mut()
+                    .unwrap()
+                    .push((start_column + 1, &line[start_index..index]));
+            }
+            if c == '|' {
+                segments.push(Vec::new());
+            }
+        } else if word_start.is_none() {
+            word_start = Some((column, index));
+        }
+    }
+    if let Some((start_column, start_index)) = word_start {
+        segments
+            .last_mut()
+            .unwrap()
+            .push((start_column + 1, &line[start_index..]));
+    }
+    segments
+}
+
+// reads a note name and any accidental from the start of the string, returning what is left over
+fn parse_note(input: &str) -> Option<(NoteName, Option<Accidental>, &str)> {
+    let name = NoteName::try_from(input.get(..1)?).ok()?;
+    let rest = &input[1..];
+    // the longer accidentals come first so that `##` isn't read as `#`
+    for accidental in ["##", "x", "bb", "#", "b", "n"] {
+        if let Some(rest) = rest.strip_prefix(accidental) {
+            return Some((name, parse_accidental(accidental), rest));
+        }
+    }
+    Some((name, None, rest))
+}
+
+fn parse_accidental(input: &str) -> Option<Accidental> {
+    match input {
+        "bb" => Some(Accidental::DoubleFlat),
+        "b" => Some(Accidental::Flat),
+        "n" => Some(Accidental::Natural),
+        "#" => Some(Accidental::Sharp),
+        "##" | "x" => Some(Accidental::DoubleSharp),
+        _ => None,
+    }
+}
+
+fn parse_figure(input: &str) -> Option<(u32, Option<Accidental>)> {
+    // an accidental on its own applies to the third
+    if let Some(accidental) = parse_accidental(input) {
+        return Some((3, Some(accidental)));
+    }
+    // the accidental can be written either before or after the number, e.g. `#6` or `6#`
+    let digits_start = input.find(|c: char| c.is_ascii_digit())?;
+    let digits_end = input[digits_start..]
+        .find(|c: char| !c.is_ascii_digit())
+        .map_or(input.len(), |x| x + digits_start);
+    let number: u32 = input[digits_start..digits_end].parse().ok()?;
+    if !(2..=9).contains(&number) {
+        return None;
+    }
+    let accidental = match (&input[..digits_start], &input[digits_end..]) {
+        ("", "") => None,
+        (prefix, "") => Some(parse_accidental(prefix)?),
+        ("", suffix) => Some(parse_accidental(suffix)?),
+        _ => return None,
+    };
+    Some((number, accidental))
+}
+
+fn parse_key_signature(line: usize, words: &[(usize, &str)]) -> Result<KeySignature, ParseError> {
+    let (column, word) = words[0];
+    let key = word.strip_prefix("key=").ok_or_else(|| {
+        ParseError::new(
+            line,
+            column,
+            format!("expected a key signature like `key=G major`, found `{word}`"),
+        )
+    })?;
+    let key_column = column + "key=".len();
+    let (name, accidental) = match parse_note(key) {
+        Some((name, accidental, "")) => (name, accidental),
+        _ => {
+            return Err(ParseError::new(
+                line,
+                key_column,
+                format!("invalid key note `{key}`"),
+            ))
+        }
+    };
+    let tonality = match words.get(1) {
+        Some((column, tonality)) => Tonality::try_from(*tonality).map_err(|_| {
+            ParseError::new(
+                line,
+                *column,
+                format!("expected `major`, `minor` or a mode like `dorian`, found `{tonality}`"),
+            )
+        })?,
+        None => {
+            return Err(ParseError::new(
+                line,
+                column + word.chars().count(),
+                "expected `major`, `minor` or a mode like `dorian` after the key note",
+            ))
+        }
+    };
+    if let Some((column, word)) = words.get(2) {
+        return Err(ParseError::new(
+            line,
+            *column,
+            format!("unexpected `{word}` after the key signature"),
+        ));
+    }
+    KeySignature::from_note(name, accidental, tonality)
+        .ok_or_else(|| ParseError::new(line, key_column, format!("unsupported key `{key}`")))
+}
+
+fn parse_bass(line: usize, words: &[(usize, &str)]) -> Result<Figure, ParseError> {
+    let (column, word) = words[0];
+    let (name, accidental, octave) = parse_note(word)
+        .ok_or_else(|| ParseError::new(line, column, format!("invalid bass note `{word}`")))?;
+    let octave_column = column + word.chars().count() - octave.chars().count();
+    let octave: u32 = match octave.parse() {
+        Ok(octave) if octave <= 8 => octave,
+        _ if octave.is_empty() => {
+            return Err(ParseError::new(
+                line,
+                octave_column,
+                format!("missing octave for bass note `{word}`"),
+            ))
+        }
+        _ => {
+            return Err(ParseError::new(
+                line,
+                octave_column,
+                format!("invalid octave `{octave}`"),
+            ))
+        }
+    };
+
+    if NoteWithOctave::new(name, accidental, octave).is_below_c0() {
+        return Err(ParseError::new(
+            line,
+            column,
+            format!("bass note `{word}` is below C0"),
+        ));
+    }
+
+    let mut figures = Vec::with_capacity(words.len() - 1);
+    for &(column, word) in words[1..].iter() {
+        // stacked figures can be written together, e.g. `6/4` or `6#/4`
+        let mut part_column = column;
+        for part in word.split('/') {
+            let figure = parse_figure(part).ok_or_else(|| {
+                ParseError::new(line, part_column, format!("invalid figure `{part}`"))
+            })?;
+            figures.push(figure);
+            part_column += part.chars().count() + 1;
+        }
+    }
+    Ok(Figure::new(name, accidental, octave, figures))
+}
+
+// parses an exercise written as e.g. `key=G major | E3 | F#3 6# | G3 6 | D#3 6 | B2 5 #3`,
+// where `|` and line breaks both separate bass notes and the figures can be abbreviated as
+// they would be in a thorough-bass part (see `Figure::expanded_figures`)
+pub fn parse_exercise(input: &str) -> Result<(KeySignature, Vec<Figure>), ParseError> {
+    let mut key_sig = None;
+    let mut figures = Vec::new();
+    for (line_index, line) in input.lines().enumerate() {
+        let line_number = line_index + 1;
+        for words in split_segments(line) {
+            if words.is_empty() {
+                continue;
+            }
+            if key_sig.is_none() {
+                key_sig = Some(parse_key_signature(line_number, &words)?);
+            } else if words[0].1.starts_with("key=") {
+                return Err(ParseError::new(
+                    line_number,
+                    words[0].0,
+                    "the key signature can only be given once",
+                ));
+            } else {
+                figures.push(parse_bass(line_number, &words)?);
+            }
+        }
+    }
+    let line_count = input.lines().count().max(1);
+    let key_sig = key_sig.ok_or_else(|| {
+        ParseError::new(line_count, 1, "expected a key signature like `key=G major`")
+    })?;
+    if figures.is_empty() {
+        return Err(ParseError::new(
+            line_count,
+            1,
+            "expected at least one bass note after the key signature",
+        ));
+    }
+    Ok((key_sig, figures))
 }
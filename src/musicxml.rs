@@ -0,0 +1,404 @@
+use crate::machine_learning::Realisation;
+use crate::music_theory::*;
+use roxmltree::{Document, Node, ParsingOptions};
+use std::fmt::Write;
+
+fn accidental_name(accidental: Accidental) -> &'static str {
+    match accidental {
+        Accidental::DoubleFlat => "flat-flat",
+        Accidental::Flat => "flat",
+        Accidental::Natural => "natural",
+        Accidental::Sharp => "sharp",
+        Accidental::DoubleSharp => "double-sharp",
+    }
+}
+
+fn write_figured_bass(xml: &mut String, figure: &Figure) {
+    if figure.figures.is_empty() {
+        return;
+    }
+    xml.push_str("      <figured-bass>\n");
+    // musicxml lists figures from top to bottom
+    for &(number, accidental) in figure.figures.iter().rev() {
+        xml.push_str("        <figure>\n");
+        if let Some(accidental) = accidental {
+            let _ = writeln!(
+                xml,
+                "          <prefix>{}</prefix>",
+                accidental_name(accidental)
+            );
+        }
+        if figure.lone_accidental().is_none() {
+            let _ = writeln!(xml, "          <figure-number>{number}</figure-number>");
+        }
+        xml.push_str("        </figure>\n");
+    }
+    xml.push_str("      </figured-bass>\n");
+}
+
+fn write_note(xml: &mut String, note: Option<NoteWithOctave>, voice: u32, staff: u32) {
+    xml.push_str("      <note>\n");
+    match note {
+        Some(note) => {
+            let step: &str = note.note.name.into();
+            xml.push_str("        <pitch>\n");
+            let _ = writeln!(xml, "          <step>{step}</step>");
+            if let Some(accidental) = note.note.accidental {
+                let _ = writeln!(xml, "          <alter>{}</alter>", accidental as i32);
+            }
+            let _ = writeln!(xml, "          <octave>{}</octave>", note.octave);
+            xml.push_str("        </pitch>\n");
+        }
+        None => xml.push_str("        <rest/>\n"),
+    }
+    xml.push_str("        <duration>1</duration>\n");
+    let _ = writeln!(xml, "        <voice>{voice}</voice>");
+    xml.push_str("        <type>quarter</type>\n");
+    // the upper voice on each staff has its stems up, the lower voice down
+    let stem = if voice % 2 == 1 { "up" } else { "down" };
+    let _ = writeln!(xml, "        <stem>{stem}</stem>");
+    let _ = writeln!(xml, "        <staff>{staff}</staff>");
+    xml.push_str("      </note>\n");
+}
+
+// writes the realisation as a two-staff SATB score, with soprano and alto on the treble staff,
+// tenor and bass on the bass staff, and the figures attached to the bass line
+pub fn to_musicxml(realisation: &Realisation, figures: &[Figure], key_sig: KeySignature) -> String {
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>\n");
+    xml.push_str("<!DOCTYPE score-partwise PUBLIC \"-//Recordare//DTD MusicXML 4.0 Partwise//EN\" \"http://www.musicxml.org/dtds/partwise.dtd\">\n");
+    xml.push_str("<score-partwise version=\"4.0\">\n");
+    xml.push_str("  <part-list>\n");
+    xml.push_str("    <score-part id=\"P1\">\n");
+    xml.push_str("      <part-name>SATB</part-name>\n");
+    xml.push_str("    </score-part>\n");
+    xml.push_str("  </part-list>\n");
+    xml.push_str("  <part id=\"P1\">\n");
+
+    let mode: &str = key_sig.tonality.into();
+    for (measure_index, chords) in realisation.chords.chunks(CHORDS_PER_MEASURE).enumerate() {
+        let _ = writeln!(xml, "    <measure number=\"{}\">", measure_index + 1);
+        if measure_index == 0 {
+            xml.push_str("      <attributes>\n");
+            xml.push_str("        <divisions>1</divisions>\n");
+            let _ = writeln!(
+                xml,
+                "        <key>\n          <fifths>{}</fifths>\n          <mode>{mode}</mode>\n        </key>",
+                key_sig.fifths()
+            );
+            let _ = writeln!(
+                xml,
+                "        <time>\n          <beats>{CHORDS_PER_MEASURE}</beats>\n          <beat-type>4</beat-type>\n        </time>"
+            );
+            xml.push_str("        <staves>2</staves>\n");
+            xml.push_str("        <clef number=\"1\">\n          <sign>G</sign>\n          <line>2</line>\n        </clef>\n");
+            xml.push_str("        <clef number=\"2\">\n          <sign>F</sign>\n          <line>4</line>\n        </clef>\n");
+            xml.push_str("      </attributes>\n");
+        }
+
+        for voice in 1..=4 {
+            let staff = if voice <= 2 { 1 } else { 2 };
+            if voice > 1 {
+                let _ = writeln!(
+                    xml,
+                    "      <backup>\n        <duration>{CHORDS_PER_MEASURE}</duration>\n      </backup>"
+                );
+            }
+            for i in 0..CHORDS_PER_MEASURE {
+                let note = chords.get(i).map(|chord| match voice {
+                    1 => chord.s,
+                    2 => chord.a,
+                    3 => chord.t,
+                    _ => chord.b,
+                });
+                if voice == 4 {
+                    if let Some(figure) = figures.get(measure_index * CHORDS_PER_MEASURE + i) {
+                        write_figured_bass(&mut xml, figure);
+                    }
+                }
+                // fill out the last measure with rests
+                write_note(&mut xml, note, voice, staff);
+            }
+        }
+
+        if measure_index == realisation.chords.len().saturating_sub(1) / CHORDS_PER_MEASURE {
+            xml.push_str("      <barline location=\"right\">\n        <bar-style>light-heavy</bar-style>\n      </barline>\n");
+        }
+        xml.push_str("    </measure>\n");
+    }
+
+    xml.push_str("  </part>\n");
+    xml.push_str("</score-partwise>\n");
+    xml
+}
+
+fn error_at(doc: &Document, node: Node, message: impl Into<String>) -> ParseError {
+    let pos = doc.text_pos_at(node.range().start);
+    ParseError::new(pos.row as usize, pos.col as usize, message)
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|x| x.has_tag_name(name))
+}
+
+fn child_text<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    child(node, name)?.text().map(str::trim)
+}
+
+// the staff and voice a note belongs to, which default to 1 when they aren't given
+fn staff_and_voice<'a>(note: Node<'a, '_>) -> (&'a str, &'a str) {
+    (
+        child_text(note, "staff").unwrap_or("1"),
+        child_text(note, "voice").unwrap_or("1"),
+    )
+}
+
+fn parse_key(doc: &Document, key: Node) -> Result<KeySignature, ParseError> {
+    let fifths: i32 = child_text(key, "fifths")
+        .and_then(|x| x.parse().ok())
+        .filter(|x: &i32| (-7..=7).contains(x))
+        .ok_or_else(|| {
+            error_at(
+                doc,
+                key,
+                "unsupported key: only traditional keys with <fifths> between -7 and 7 are supported",
+            )
+        })?;
+    let tonality = match child_text(key, "mode") {
+        None => Tonality::Major,
+        Some(mode) => Tonality::try_from(mode).map_err(|_| {
+            error_at(
+                doc,
+                key,
+                format!("unsupported mode `{mode}`, only major, minor and the church modes are supported"),
+            )
+        })?,
+    };
+    // `fifths` has already been checked to be in range
+    Ok(KeySignature::from_fifths(fifths, tonality).unwrap())
+}
+
+fn parse_figure_accidental(doc: &Document, node: Node) -> Result<Accidental, ParseError> {
+    match node.text().map(str::trim) {
+        Some("flat-flat") | Some("double-flat") => Ok(Accidental::DoubleFlat),
+        Some("flat") => Ok(Accidental::Flat),
+        Some("natural") => Ok(Accidental::Natural),
+        Some("sharp") => Ok(Accidental::Sharp),
+        Some("double-sharp") | Some("sharp-sharp") => Ok(Accidental::DoubleSharp),
+        // a slashed or crossed figure means the interval is raised
+        Some("plus") | Some("slash") | Some("backslash") => Ok(Accidental::Sharp),
+        Some(other) => Err(error_at(
+            doc,
+            node,
+            format!("unsupported figure accidental `{other}`"),
+        )),
+        None => Err(error_at(doc, node, "empty figure accidental")),
+    }
+}
+
+fn parse_figured_bass(
+    doc: &Document,
+    figured_bass: Node,
+) -> Result<Vec<(u32, Option<Accidental>)>, ParseError> {
+    let mut figures = Vec::new();
+    for figure in figured_bass.children().filter(|x| x.has_tag_name("figure")) {
+        let number = match child(figure, "figure-number") {
+            Some(number) => Some(
+                number
+                    .text()
+                    .and_then(|x| x.trim().parse().ok())
+                    .filter(|x| (2..=9).contains(x))
+                    .ok_or_else(|| {
+                        error_at(
+                            doc,
+                            number,
+                            "unsupported figure number, only 2 to 9 are supported",
+                        )
+                    })?,
+            ),
+            None => None,
+        };
+        let accidental = match (child(figure, "prefix"), child(figure, "suffix")) {
+            (Some(prefix), None) => Some(parse_figure_accidental(doc, prefix)?),
+            (None, Some(suffix)) => Some(parse_figure_accidental(doc, suffix)?),
+            (None, None) => None,
+            (Some(_), Some(_)) => {
+                return Err(error_at(
+                    doc,
+                    figure,
+                    "figures with both a prefix and a suffix are not supported",
+                ))
+            }
+        };
+        match (number, accidental) {
+            (Some(number), accidental) => figures.push((number, accidental)),
+            // a lone accidental is an altered third
+            (None, Some(accidental)) => figures.push((3, Some(accidental))),
+            // an extension line only continues the previous figure, so there's nothing to add
+            (None, None) if child(figure, "extend").is_some() => {}
+            (None, None) => return Err(error_at(doc, figure, "empty figure")),
+        }
+    }
+    Ok(figures)
+}
+
+fn parse_bass_note(doc: &Document, note: Node) -> Result<NoteWithOctave, ParseError> {
+    for (name, description) in [
+        ("chord", "chords in the bass line"),
+        ("grace", "grace notes in the bass line"),
+        ("cue", "cue notes in the bass line"),
+        ("unpitched", "unpitched notes in the bass line"),
+    ] {
+        if child(note, name).is_some() {
+            return Err(error_at(
+                doc,
+                note,
+                format!("{description} are not supported"),
+            ));
+        }
+    }
+    if note
+        .children()
+        .any(|x| x.has_tag_name("tie") && x.attribute("type") == Some("stop"))
+    {
+        return Err(error_at(
+            doc,
+            note,
+            "tied notes in the bass line are not supported",
+        ));
+    }
+
+    let pitch = child(note, "pitch").ok_or_else(|| error_at(doc, note, "note has no <pitch>"))?;
+    let name = child_text(pitch, "step")
+        .and_then(|x| NoteName::try_from(x).ok())
+        .ok_or_else(|| error_at(doc, pitch, "invalid or missing <step>"))?;
+    let accidental = match child_text(pitch, "alter") {
+        None => None,
+        Some(alter) => match alter.parse().ok().and_then(Accidental::from_alteration) {
+            Some(accidental) => Some(accidental),
+            None => {
+                return Err(error_at(
+                    doc,
+                    pitch,
+                    format!("unsupported <alter> `{alter}`, only -2 to 2 are supported"),
+                ))
+            }
+        },
+    };
+    let octave = child_text(pitch, "octave")
+        .and_then(|x| x.parse().ok())
+        .filter(|x| *x <= 8)
+        .ok_or_else(|| error_at(doc, pitch, "invalid or missing <octave>"))?;
+    let note = NoteWithOctave::new(name, accidental, octave);
+    if note.is_below_c0() {
+        return Err(error_at(doc, pitch, "notes below C0 aren't supported"));
+    }
+    Ok(note)
+}
+
+// reads a figured bass exercise from a musicxml file, taking the bass line to be the voice that
+// the <figured-bass> elements are attached to
+pub fn from_musicxml(xml: &str) -> Result<(KeySignature, Vec<Figure>), ParseError> {
+    let options = ParsingOptions {
+        allow_dtd: true,
+        ..ParsingOptions::default()
+    };
+    let doc = Document::parse_with_options(xml, options).map_err(|err| {
+        let pos = err.pos();
+        ParseError::new(pos.row as usize, pos.col as usize, err.to_string())
+    })?;
+    let root = doc.root_element();
+    if !root.has_tag_name("score-partwise") {
+        return Err(error_at(
+            &doc,
+            root,
+            format!(
+                "unsupported root element <{}>, only <score-partwise> is supported",
+                root.tag_name().name()
+            ),
+        ));
+    }
+
+    // find the part, staff and voice of the first note with figures
+    let mut bass_line = None;
+    'parts: for part in root.children().filter(|x| x.has_tag_name("part")) {
+        let mut found_figures = false;
+        for measure in part.children().filter(|x| x.has_tag_name("measure")) {
+            for element in measure.children() {
+                if element.has_tag_name("figured-bass") {
+                    found_figures = true;
+                } else if element.has_tag_name("note") && found_figures {
+                    bass_line = Some((part, staff_and_voice(element)));
+                    break 'parts;
+                }
+            }
+        }
+    }
+    let (part, bass_staff_and_voice) =
+        bass_line.ok_or_else(|| error_at(&doc, root, "no <figured-bass> elements found"))?;
+
+    let mut key = None;
+    let mut figures = Vec::new();
+    let mut pending_figures = None;
+    let mut rest = None;
+    for measure in part.children().filter(|x| x.has_tag_name("measure")) {
+        for element in measure.children() {
+            if element.has_tag_name("attributes") {
+                if let Some(key_node) = child(element, "key") {
+                    let key_sig = parse_key(&doc, key_node)?;
+                    match key {
+                        None => key = Some((key_sig, key_node)),
+                        Some((first, _)) if first == key_sig => {}
+                        Some(_) => {
+                            return Err(error_at(&doc, key_node, "key changes are not supported"))
+                        }
+                    }
+                }
+            } else if element.has_tag_name("figured-bass") {
+                if pending_figures.is_some() {
+                    return Err(error_at(
+                        &doc,
+                        element,
+                        "more than one <figured-bass> for a single bass note is not supported",
+                    ));
+                }
+                pending_figures = Some(element);
+            } else if element.has_tag_name("note")
+                && staff_and_voice(element) == bass_staff_and_voice
+            {
+                // rests are only allowed after the last bass note, e.g. to fill out the last bar
+                if child(element, "rest").is_some() {
+                    rest = Some(element);
+                    continue;
+                }
+                if let Some(rest) = rest {
+                    return Err(error_at(
+                        &doc,
+                        rest,
+                        "rests in the middle of the bass line are not supported",
+                    ));
+                }
+                let bass = parse_bass_note(&doc, element)?;
+                let bass_figures = match pending_figures.take() {
+                    Some(figured_bass) => parse_figured_bass(&doc, figured_bass)?,
+                    None => Vec::new(),
+                };
+                figures.push(Figure::new(
+                    bass.note.name,
+                    bass.note.accidental,
+                    bass.octave,
+                    bass_figures,
+                ));
+            }
+        }
+    }
+    if let Some(figured_bass) = pending_figures {
+        return Err(error_at(
+            &doc,
+            figured_bass,
+            "<figured-bass> is not followed by a bass note",
+        ));
+    }
+    let (key_sig, _) = key.ok_or_else(|| error_at(&doc, part, "no key signature found"))?;
+    Ok((key_sig, figures))
+}
@@ -0,0 +1,1141 @@
+// the rules that `Scorer::default` is made up of
+use crate::music_theory::*;
+use crate::scoring::{Context, Rule, Voice};
+
+// every voice on its own, from the top down
+static VOICES: [[Voice; 1]; 4] = [
+    [Voice::Soprano],
+    [Voice::Alto],
+    [Voice::Tenor],
+    [Voice::Bass],
+];
+
+// every pair of voices, lower voice first
+static VOICE_PAIRS: [[Voice; 2]; 6] = [
+    [Voice::Bass, Voice::Tenor],
+    [Voice::Bass, Voice::Alto],
+    [Voice::Bass, Voice::Soprano],
+    [Voice::Tenor, Voice::Alto],
+    [Voice::Tenor, Voice::Soprano],
+    [Voice::Alto, Voice::Soprano],
+];
+
+// the pairs of voices that are next to each other, lower voice first
+static ADJACENT_VOICE_PAIRS: [[Voice; 2]; 3] = [
+    [Voice::Bass, Voice::Tenor],
+    [Voice::Tenor, Voice::Alto],
+    [Voice::Alto, Voice::Soprano],
+];
+
+// the distance between two notes in semitones with compound intervals reduced to within an
+// octave, so a twelfth counts as a fifth and a double octave counts as an octave
+fn simple_interval(a: NoteWithOctave, b: NoteWithOctave) -> u32 {
+    a.semitones_between(b) % 12
+}
+
+// whether a melodic interval is augmented or diminished, going by how the notes are spelt
+// rather than just how far apart they are, so F to G# is an augmented second but F to Ab is a
+// minor third. chromatic semitones like C to C# don't count.
+fn is_augmented_or_diminished(a: NoteWithOctave, b: NoteWithOctave) -> bool {
+    let interval = Interval::between(a, b);
+    interval.compound_number() != 1 && interval.is_augmented_or_diminished()
+}
+
+// the pairs of voices that both move from a perfect fifth or octave to the same interval (or a
+// compound of it), with the interval (0 or 7), whether the voices move in opposite directions,
+// and whether they are at a unison both times
+fn consecutive_perfect_intervals(
+    prev_chord: &Chord,
+    chord: &Chord,
+) -> Vec<(&'static [Voice], u32, bool, bool)> {
+    let mut found = Vec::new();
+    for pair in VOICE_PAIRS.iter() {
+        let [lower, upper] = *pair;
+        let lower_motion = lower.note(prev_chord).semitones_up_to(lower.note(chord));
+        let upper_motion = upper.note(prev_chord).semitones_up_to(upper.note(chord));
+        if lower_motion == 0 || upper_motion == 0 {
+            continue;
+        }
+        let prev_interval = simple_interval(lower.note(prev_chord), upper.note(prev_chord));
+        let interval = simple_interval(lower.note(chord), upper.note(chord));
+        if prev_interval != interval || !(interval == 0 || interval == 7) {
+            continue;
+        }
+        let is_unison = lower.note(chord).semitones_between(upper.note(chord)) == 0
+            && lower
+                .note(prev_chord)
+                .semitones_between(upper.note(prev_chord))
+                == 0;
+        let is_contrary = lower_motion.signum() != upper_motion.signum();
+        found.push((pair as &'static [Voice], interval, is_contrary, is_unison));
+    }
+    found
+}
+
+// each note that is in more than one voice, and the first pair of voices it's in
+fn doubled_notes(chord: &Chord) -> Vec<(u32, &'static [Voice])> {
+    let mut doubled: Vec<(u32, &'static [Voice])> = Vec::new();
+    for pair in VOICE_PAIRS.iter() {
+        let [lower, upper] = *pair;
+        let note = lower.note(chord).note.to_semitones();
+        if note == upper.note(chord).note.to_semitones() && !doubled.iter().any(|x| x.0 == note) {
+            doubled.push((note, pair));
+        }
+    }
+    doubled
+}
+
+// two voices next to each other on exactly the same note
+pub struct AdjacentUnisons;
+impl Rule for AdjacentUnisons {
+    fn name(&self) -> &'static str {
+        "adjacent unisons"
+    }
+    fn span(&self) -> usize {
+        1
+    }
+    fn check(&self, context: &Context, found: &mut dyn FnMut(&'static [Voice], &'static str, i32)) {
+        let chord = context.chord();
+        if let Some(pair) = ADJACENT_VOICE_PAIRS
+            .iter()
+            .find(|[lower, upper]| lower.note(chord) == upper.note(chord))
+        {
+            found(pair, "adjacent unison", 1);
+        }
+    }
+}
+
+// an upper voice outside the part of its range that is comfortable to sing
+pub struct ComfortableRange;
+impl Rule for ComfortableRange {
+    fn name(&self) -> &'static str {
+        "comfortable range"
+    }
+    fn span(&self) -> usize {
+        1
+    }
+    fn check(&self, context: &Context, found: &mut dyn FnMut(&'static [Voice], &'static str, i32)) {
+        let ranges = [
+            (NoteName::E, 4, NoteName::E, 5),
+            (NoteName::B, 3, NoteName::B, 4),
+            (NoteName::G, 3, NoteName::G, 4),
+        ];
+        for (voices, (lb_name, lb_octave, ub_name, ub_octave)) in VOICES.iter().zip(ranges) {
+            if !voices[0].note(context.chord()).in_range_inclusive(
+                NoteWithOctave::new(lb_name, None, lb_octave),
+                NoteWithOctave::new(ub_name, None, ub_octave),
+            ) {
+                found(voices, "outside comfortable range", 1);
+            }
+        }
+    }
+}
+
+// rewards the upper voices for moving by small intervals and takes points away for larger ones,
+// with the soprano counting twice as much
+pub struct MelodicMotion;
+impl Rule for MelodicMotion {
+    fn name(&self) -> &'static str {
+        "melodic motion"
+    }
+    fn span(&self) -> usize {
+        2
+    }
+    fn check(&self, context: &Context, found: &mut dyn FnMut(&'static [Voice], &'static str, i32)) {
+        let (prev_chord, chord) = (context.prev_chord(), context.chord());
+        for (voices, (factor, limit)) in VOICES.iter().zip([(2, 8), (1, 4), (1, 4)]) {
+            let interval = voices[0]
+                .note(prev_chord)
+                .semitones_between(voices[0].note(chord)) as i32;
+            found(voices, "melodic motion", limit - factor * interval);
+        }
+    }
+}
+
+// two voices moving in the same direction from one perfect fifth (or compound fifth) to another
+pub struct ParallelFifths;
+impl Rule for ParallelFifths {
+    fn name(&self) -> &'static str {
+        "parallel fifths"
+    }
+    fn span(&self) -> usize {
+        2
+    }
+    fn check(&self, context: &Context, found: &mut dyn FnMut(&'static [Voice], &'static str, i32)) {
+        for (voices, interval, is_contrary, _) in
+            consecutive_perfect_intervals(context.prev_chord(), context.chord())
+        {
+            if interval == 7 && !is_contrary {
+                found(voices, "parallel fifths", 1);
+            }
+        }
+    }
+}
+
+// the same as `ParallelFifths` for octaves and unisons
+pub struct ParallelOctaves;
+impl Rule for ParallelOctaves {
+    fn name(&self) -> &'static str {
+        "parallel octaves"
+    }
+    fn span(&self) -> usize {
+        2
+    }
+    fn check(&self, context: &Context, found: &mut dyn FnMut(&'static [Voice], &'static str, i32)) {
+        for (voices, interval, is_contrary, is_unison) in
+            consecutive_perfect_intervals(context.prev_chord(), context.chord())
+        {
+            if interval == 0 && !is_contrary {
+                if is_unison {
+                    found(voices, "parallel unisons", 1);
+                } else {
+                    found(voices, "parallel octaves", 1);
+                }
+            }
+        }
+    }
+}
+
+// two voices moving in opposite directions from one perfect fifth or octave to another, e.g.
+// from a fifth to a twelfth
+pub struct ContraryFifthsAndOctaves;
+impl Rule for ContraryFifthsAndOctaves {
+    fn name(&self) -> &'static str {
+        "contrary fifths and octaves"
+    }
+    fn span(&self) -> usize {
+        2
+    }
+    fn check(&self, context: &Context, found: &mut dyn FnMut(&'static [Voice], &'static str, i32)) {
+        for (voices, interval, is_contrary, _) in
+            consecutive_perfect_intervals(context.prev_chord(), context.chord())
+        {
+            if is_contrary {
+                if interval == 0 {
+                    found(voices, "contrary octaves", 1);
+                } else {
+                    found(voices, "contrary fifths", 1);
+                }
+            }
+        }
+    }
+}
+
+// the bass and soprano moving in the same direction into a perfect fifth or octave (that they
+// weren't already at), with the soprano leaping
+pub struct HiddenFifthsAndOctaves;
+impl Rule for HiddenFifthsAndOctaves {
+    fn name(&self) -> &'static str {
+        "hidden fifths and octaves"
+    }
+    fn span(&self) -> usize {
+        2
+    }
+    fn check(&self, context: &Context, found: &mut dyn FnMut(&'static [Voice], &'static str, i32)) {
+        let (prev_chord, chord) = (context.prev_chord(), context.chord());
+        let b_motion = prev_chord.b.semitones_up_to(chord.b);
+        let s_motion = prev_chord.s.semitones_up_to(chord.s);
+        let interval = chord.b.semitones_up_to(chord.s).rem_euclid(12);
+        let prev_interval = prev_chord.b.semitones_up_to(prev_chord.s).rem_euclid(12);
+        if b_motion.signum() == s_motion.signum()
+            && b_motion != 0
+            && s_motion.abs() > 2
+            && (interval == 0 || interval == 7)
+            && interval != prev_interval
+        {
+            let description = if interval == 0 {
+                "hidden octaves"
+            } else {
+                "hidden fifths"
+            };
+            found(&[Voice::Bass, Voice::Soprano], description, 1);
+        }
+    }
+}
+
+// a voice below the voice that should be under it within a chord, e.g. the alto below the
+// tenor. the solvers never cross voices themselves, so this only matters when scoring other
+// realisations, and it can be removed for styles that allow crossing.
+pub struct VoiceCrossing;
+impl Rule for VoiceCrossing {
+    fn name(&self) -> &'static str {
+        "voice crossing"
+    }
+    fn span(&self) -> usize {
+        1
+    }
+    fn check(&self, context: &Context, found: &mut dyn FnMut(&'static [Voice], &'static str, i32)) {
+        let chord = context.chord();
+        for pair in ADJACENT_VOICE_PAIRS.iter() {
+            let [lower, upper] = *pair;
+            if lower.note(chord).semitones_up_to(upper.note(chord)) < 0 {
+                found(pair, "voice crossing", 1);
+            }
+        }
+    }
+}
+
+// a voice moving past where the voice next to it was in the previous chord
+pub struct VoiceOverlap;
+impl Rule for VoiceOverlap {
+    fn name(&self) -> &'static str {
+        "voice overlap"
+    }
+    fn span(&self) -> usize {
+        2
+    }
+    fn check(&self, context: &Context, found: &mut dyn FnMut(&'static [Voice], &'static str, i32)) {
+        let (prev_chord, chord) = (context.prev_chord(), context.chord());
+        for pair in ADJACENT_VOICE_PAIRS.iter() {
+            let [lower, upper] = *pair;
+            if upper.note(prev_chord).semitones_up_to(lower.note(chord)) > 0
+                || upper.note(chord).semitones_up_to(lower.note(prev_chord)) > 0
+            {
+                found(pair, "voice overlap", 1);
+            }
+        }
+    }
+}
+
+// adjacent voices further apart than they are allowed to be
+pub struct Spacing {
+    // the most semitones allowed between the bass and tenor, tenor and alto, and alto and
+    // soprano, or None for no limit
+    pub max_spacing: [Option<u32>; 3],
+}
+impl Default for Spacing {
+    fn default() -> Self {
+        Self {
+            // the upper voices are kept within an octave of each other, but the tenor and bass
+            // can be as far apart as they like
+            max_spacing: [None, Some(12), Some(12)],
+        }
+    }
+}
+impl Rule for Spacing {
+    fn name(&self) -> &'static str {
+        "spacing"
+    }
+    fn span(&self) -> usize {
+        1
+    }
+    fn check(&self, context: &Context, found: &mut dyn FnMut(&'static [Voice], &'static str, i32)) {
+        let chord = context.chord();
+        for (pair, max_spacing) in ADJACENT_VOICE_PAIRS.iter().zip(self.max_spacing) {
+            let [lower, upper] = *pair;
+            if let Some(max_spacing) = max_spacing {
+                if lower.note(chord).semitones_between(upper.note(chord)) > max_spacing {
+                    found(pair, "spacing", 1);
+                }
+            }
+        }
+    }
+}
+
+// a leading tone in the soprano that doesn't rise a semitone to the tonic when the next chord has
+// the tonic in it, or a note the figures raised that doesn't rise a semitone, since it is the
+// leading tone of whatever it is raised towards (although either can stay where it is). the bass
+// is given by the exercise, so it isn't checked.
+pub struct LeadingToneResolution;
+impl Rule for LeadingToneResolution {
+    fn name(&self) -> &'static str {
+        "leading tone resolution"
+    }
+    fn span(&self) -> usize {
+        2
+    }
+    fn check(&self, context: &Context, found: &mut dyn FnMut(&'static [Voice], &'static str, i32)) {
+        let (prev_chord, chord) = (context.prev_chord(), context.chord());
+        let (prev_input, input) = (context.prev_input(), context.input());
+        let leading_tone = prev_input.key_sig.leading_tone().to_semitones();
+        let tonic = input.key_sig.get_starting_note().to_semitones();
+        let has_tonic = input.notes.iter().any(|x| x.to_semitones() == tonic);
+
+        let voices = &VOICES[0];
+        let prev_note = voices[0].note(prev_chord);
+        let motion = prev_note.semitones_up_to(voices[0].note(chord));
+        let is_leading_tone = (has_tonic && prev_note.note.to_semitones() == leading_tone)
+            || prev_input
+                .raised_notes
+                .iter()
+                .any(|x| x.to_semitones() == prev_note.note.to_semitones());
+        if is_leading_tone && motion != 0 && motion != 1 {
+            found(voices, "unresolved leading tone", 1);
+        }
+    }
+}
+
+// the seventh of a chord in an upper voice that doesn't fall by a step into the next chord, unless
+// it is held over as the seventh of the next chord (the seventh of a 4/2 chord is in the bass,
+// which is given by the exercise)
+pub struct SeventhResolution;
+impl Rule for SeventhResolution {
+    fn name(&self) -> &'static str {
+        "seventh resolution"
+    }
+    fn span(&self) -> usize {
+        2
+    }
+    fn check(&self, context: &Context, found: &mut dyn FnMut(&'static [Voice], &'static str, i32)) {
+        let (prev_chord, chord) = (context.prev_chord(), context.chord());
+        let Some(seventh) = context.prev_input().seventh else {
+            return;
+        };
+        for voices in VOICES[..3].iter() {
+            let prev_note = voices[0].note(prev_chord);
+            if prev_note.note.to_semitones() != seventh.to_semitones() {
+                continue;
+            }
+            let motion = prev_note.semitones_up_to(voices[0].note(chord));
+            let held_over = motion == 0
+                && context
+                    .input()
+                    .seventh
+                    .is_some_and(|x| x.to_semitones() == seventh.to_semitones());
+            if !(motion == -1 || motion == -2 || held_over) {
+                found(voices, "unresolved seventh", 1);
+            }
+        }
+    }
+}
+
+// an upper voice moving by an augmented or diminished interval (the bass is given by the
+// exercise, so it isn't checked)
+pub struct AugmentedOrDiminishedInterval;
+impl Rule for AugmentedOrDiminishedInterval {
+    fn name(&self) -> &'static str {
+        "augmented or diminished interval"
+    }
+    fn span(&self) -> usize {
+        2
+    }
+    fn check(&self, context: &Context, found: &mut dyn FnMut(&'static [Voice], &'static str, i32)) {
+        for voices in VOICES[..3].iter() {
+            let prev_note = voices[0].note(context.prev_chord());
+            if is_augmented_or_diminished(prev_note, voices[0].note(context.chord())) {
+                found(voices, "augmented or diminished interval", 1);
+            }
+        }
+    }
+}
+
+// an upper voice leaping by more than an octave
+pub struct LargeLeap;
+impl Rule for LargeLeap {
+    fn name(&self) -> &'static str {
+        "large leap"
+    }
+    fn span(&self) -> usize {
+        2
+    }
+    fn check(&self, context: &Context, found: &mut dyn FnMut(&'static [Voice], &'static str, i32)) {
+        for voices in VOICES[..3].iter() {
+            let prev_note = voices[0].note(context.prev_chord());
+            if prev_note.semitones_between(voices[0].note(context.chord())) > 12 {
+                found(voices, "leap larger than an octave", 1);
+            }
+        }
+    }
+}
+
+// an upper voice leaping by more than a fourth, and then not moving by step the other way
+pub struct LeapRecovery;
+impl Rule for LeapRecovery {
+    fn name(&self) -> &'static str {
+        "leap recovery"
+    }
+    fn span(&self) -> usize {
+        3
+    }
+    fn check(&self, context: &Context, found: &mut dyn FnMut(&'static [Voice], &'static str, i32)) {
+        let [first_chord, prev_chord, chord] = context.chords else {
+            return;
+        };
+        for voices in VOICES[..3].iter() {
+            let leap = voices[0]
+                .note(first_chord)
+                .semitones_up_to(voices[0].note(prev_chord));
+            let motion = voices[0]
+                .note(prev_chord)
+                .semitones_up_to(voices[0].note(chord));
+            if leap.abs() > 5
+                && !(motion.signum() == -leap.signum() && (motion.abs() == 1 || motion.abs() == 2))
+            {
+                found(voices, "leap not followed by a step back", 1);
+            }
+        }
+    }
+}
+
+// in a minor key realised with the melodic minor scale, an upper voice that rises by step from
+// the sixth or seventh degree without it being raised, or falls by step from it when it is
+pub struct MelodicMinor;
+impl Rule for MelodicMinor {
+    fn name(&self) -> &'static str {
+        "melodic minor"
+    }
+    fn span(&self) -> usize {
+        2
+    }
+    fn check(&self, context: &Context, found: &mut dyn FnMut(&'static [Voice], &'static str, i32)) {
+        let input = context.prev_input();
+        if input.minor_scale != MinorScale::Melodic || input.key_sig.tonality != Tonality::Minor {
+            return;
+        }
+        let tonic = input.key_sig.get_starting_note().to_semitones();
+        for voices in VOICES[..3].iter() {
+            let prev_note = voices[0].note(context.prev_chord());
+            // how many semitones above the tonic the note is
+            let degree = (prev_note.note.to_semitones() + 12 - tonic) % 12;
+            match (
+                degree,
+                prev_note.steps_up_to(voices[0].note(context.chord())),
+            ) {
+                (8 | 10, 1) => found(voices, "unraised sixth or seventh rising", 1),
+                (9 | 11, -1) => found(voices, "raised sixth or seventh falling", 1),
+                _ => {}
+            }
+        }
+    }
+}
+
+// two voices with the leading tone in the same chord
+pub struct LeadingToneDoubling;
+impl Rule for LeadingToneDoubling {
+    fn name(&self) -> &'static str {
+        "leading tone doubling"
+    }
+    fn span(&self) -> usize {
+        1
+    }
+    fn check(&self, context: &Context, found: &mut dyn FnMut(&'static [Voice], &'static str, i32)) {
+        let leading_tone = context.input().key_sig.leading_tone().to_semitones();
+        for (note, voices) in doubled_notes(context.chord()) {
+            if note == leading_tone {
+                found(voices, "doubled leading tone", 1);
+            }
+        }
+    }
+}
+
+// two voices with a chromatic note, which the figures raised or lowered or which is outside the
+// key (other than the leading tone, which `LeadingToneDoubling` covers)
+pub struct ChromaticDoubling;
+impl Rule for ChromaticDoubling {
+    fn name(&self) -> &'static str {
+        "chromatic doubling"
+    }
+    fn span(&self) -> usize {
+        1
+    }
+    fn check(&self, context: &Context, found: &mut dyn FnMut(&'static [Voice], &'static str, i32)) {
+        let input = context.input();
+        let leading_tone = input.key_sig.leading_tone().to_semitones();
+        for (note, voices) in doubled_notes(context.chord()) {
+            if note != leading_tone && input.altered_notes.iter().any(|x| x.to_semitones() == note)
+            {
+                found(voices, "doubled chromatic note", 1);
+            }
+        }
+    }
+}
+
+// doubling a note other than the root of a 5/3 chord or the bass of a 6/4 chord (when the note
+// isn't one that shouldn't be doubled at all)
+pub struct PreferredDoubling;
+impl Rule for PreferredDoubling {
+    fn name(&self) -> &'static str {
+        "preferred doubling"
+    }
+    fn span(&self) -> usize {
+        1
+    }
+    fn check(&self, context: &Context, found: &mut dyn FnMut(&'static [Voice], &'static str, i32)) {
+        let input = context.input();
+        let Some(preferred) = input.preferred_doubling else {
+            return;
+        };
+        let leading_tone = input.key_sig.leading_tone().to_semitones();
+        for (note, voices) in doubled_notes(context.chord()) {
+            if note != leading_tone
+                && !input.altered_notes.iter().any(|x| x.to_semitones() == note)
+                && note != preferred.to_semitones()
+            {
+                found(voices, "unpreferred doubling", 1);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machine_learning::ChordInput;
+    use crate::scoring::Finding;
+
+    // a chord from its notes from the soprano down, e.g. "G4 D4 B3 G2"
+    fn chord(notes: &str) -> Chord {
+        let notes: Vec<NoteWithOctave> = notes
+            .split(' ')
+            .map(|note| {
+                let name = NoteName::try_from(&note[..1]).unwrap();
+                let (accidental, octave) = match &note[1..2] {
+                    "#" => (Some(Accidental::Sharp), &note[2..]),
+                    "b" => (Some(Accidental::Flat), &note[2..]),
+                    _ => (None, &note[1..]),
+                };
+                NoteWithOctave::new(name, accidental, octave.parse().unwrap())
+            })
+            .collect();
+        Chord::new(notes[0], notes[1], notes[2], notes[3])
+    }
+
+    // what the rule finds at the last of `chords`, which are a realisation of `exercise`
+    fn check(rule: impl Rule, exercise: &str, chords: &[&str]) -> Vec<Finding> {
+        check_with(rule, exercise, MinorScale::Natural, chords)
+    }
+
+    fn check_with(
+        rule: impl Rule,
+        exercise: &str,
+        minor_scale: MinorScale,
+        chords: &[&str],
+    ) -> Vec<Finding> {
+        let (key_sig, figures) = parse_exercise(exercise).unwrap();
+        let chords_input = ChordInput::from_figures(&figures, key_sig, minor_scale);
+        let chords: Vec<Chord> = chords.iter().map(|x| chord(x)).collect();
+        let chord_index = chords.len() - 1;
+        let context = Context {
+            chords_input: &chords_input,
+            chord_index,
+            chords: &chords[chords.len() - rule.span()..],
+        };
+        let mut findings = Vec::new();
+        rule.check(&context, &mut |voices, description, points| {
+            findings.push(Finding {
+                chord_index,
+                voices,
+                rule: rule.name(),
+                description,
+                points,
+            })
+        });
+        findings
+    }
+
+    fn finding(
+        chord_index: usize,
+        voices: &'static [Voice],
+        rule: &'static str,
+        description: &'static str,
+    ) -> Finding {
+        Finding {
+            chord_index,
+            voices,
+            rule,
+            description,
+            points: 1,
+        }
+    }
+
+    #[test]
+    fn parallel_fifths_and_octaves() {
+        let exercise = "key=C major | C3 | D3";
+        let chords = ["G4 E4 C4 C3", "A4 F4 D4 D3"];
+        assert_eq!(
+            check(ParallelFifths, exercise, &chords),
+            [
+                finding(
+                    1,
+                    &[Voice::Bass, Voice::Soprano],
+                    "parallel fifths",
+                    "parallel fifths"
+                ),
+                finding(
+                    1,
+                    &[Voice::Tenor, Voice::Soprano],
+                    "parallel fifths",
+                    "parallel fifths"
+                ),
+            ]
+        );
+        assert_eq!(
+            check(ParallelOctaves, exercise, &chords),
+            [finding(
+                1,
+                &[Voice::Bass, Voice::Tenor],
+                "parallel octaves",
+                "parallel octaves"
+            )]
+        );
+        // moving in contrary motion to the bass avoids them
+        let chords = ["G4 E4 C4 C3", "F4 D4 A3 D3"];
+        assert_eq!(check(ParallelFifths, exercise, &chords), []);
+        assert_eq!(check(ParallelOctaves, exercise, &chords), []);
+    }
+
+    #[test]
+    fn contrary_fifths_and_octaves() {
+        let chords = ["C5 G4 E4 C3", "D4 A3 F3 D3"];
+        assert_eq!(
+            check(ContraryFifthsAndOctaves, "key=C major | C3 | D3", &chords),
+            [
+                finding(
+                    1,
+                    &[Voice::Bass, Voice::Alto],
+                    "contrary fifths and octaves",
+                    "contrary fifths"
+                ),
+                finding(
+                    1,
+                    &[Voice::Bass, Voice::Soprano],
+                    "contrary fifths and octaves",
+                    "contrary octaves"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn hidden_fifths_and_octaves() {
+        let exercise = "key=C major | C3 | D3";
+        assert_eq!(
+            check(
+                HiddenFifthsAndOctaves,
+                exercise,
+                &["E4 C4 G3 C3", "D5 F4 A3 D3"]
+            ),
+            [finding(
+                1,
+                &[Voice::Bass, Voice::Soprano],
+                "hidden fifths and octaves",
+                "hidden octaves"
+            )]
+        );
+        // they're allowed when the soprano moves by step
+        assert_eq!(
+            check(
+                HiddenFifthsAndOctaves,
+                exercise,
+                &["E5 C5 G4 C3", "D5 F4 A3 D3"]
+            ),
+            []
+        );
+    }
+
+    #[test]
+    fn voice_crossing_and_spacing() {
+        let exercise = "key=C major | C3";
+        assert_eq!(
+            check(VoiceCrossing, exercise, &["E4 G4 C4 C3"]),
+            [finding(
+                0,
+                &[Voice::Alto, Voice::Soprano],
+                "voice crossing",
+                "voice crossing"
+            )]
+        );
+        assert_eq!(
+            check(Spacing::default(), exercise, &["G5 C4 E3 C3"]),
+            [finding(
+                0,
+                &[Voice::Alto, Voice::Soprano],
+                "spacing",
+                "spacing"
+            )]
+        );
+        let spacing = Spacing {
+            max_spacing: [Some(3), None, None],
+        };
+        assert_eq!(
+            check(spacing, exercise, &["G5 C4 E3 C3"]),
+            [finding(
+                0,
+                &[Voice::Bass, Voice::Tenor],
+                "spacing",
+                "spacing"
+            )]
+        );
+    }
+
+    #[test]
+    fn voice_overlap() {
+        assert_eq!(
+            check(
+                VoiceOverlap,
+                "key=C major | C3 | G2",
+                &["E4 C4 G3 C3", "B4 G4 D4 G2"]
+            ),
+            [
+                finding(
+                    1,
+                    &[Voice::Tenor, Voice::Alto],
+                    "voice overlap",
+                    "voice overlap"
+                ),
+                finding(
+                    1,
+                    &[Voice::Alto, Voice::Soprano],
+                    "voice overlap",
+                    "voice overlap"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn leading_tone_resolution() {
+        let exercise = "key=C major | G2 | C3";
+        assert_eq!(
+            check(
+                LeadingToneResolution,
+                exercise,
+                &["B4 D4 G3 G2", "G4 E4 C4 C3"]
+            ),
+            [finding(
+                1,
+                &[Voice::Soprano],
+                "leading tone resolution",
+                "unresolved leading tone"
+            )]
+        );
+        assert_eq!(
+            check(
+                LeadingToneResolution,
+                exercise,
+                &["B4 D4 G3 G2", "C5 E4 G3 C3"]
+            ),
+            []
+        );
+        // a note raised by the figures has to rise too
+        assert_eq!(
+            check(
+                LeadingToneResolution,
+                "key=C major | E3 # | A2",
+                &["G#4 B3 E3 E3", "E4 C4 A3 A2"]
+            ),
+            [finding(
+                1,
+                &[Voice::Soprano],
+                "leading tone resolution",
+                "unresolved leading tone"
+            )]
+        );
+        // the bass is given, so it isn't checked
+        assert_eq!(
+            check(
+                LeadingToneResolution,
+                "key=C major | B2 6 | A2",
+                &["G4 D4 G3 B2", "A4 E4 C4 A2"]
+            ),
+            []
+        );
+        // the raised sixth of the rising melodic minor scale goes up a tone, not a semitone
+        let exercise = "key=A minor | D3 | E3 | A2";
+        let chords = ["F#4 D4 A3 D3", "G#4 E4 B3 E3", "A4 E4 C4 A2"];
+        for i in 2..=3 {
+            assert_eq!(
+                check_with(
+                    LeadingToneResolution,
+                    exercise,
+                    MinorScale::Melodic,
+                    &chords[..i]
+                ),
+                []
+            );
+        }
+        // but the leading tone still has to rise to the tonic
+        assert_eq!(
+            check_with(
+                LeadingToneResolution,
+                exercise,
+                MinorScale::Melodic,
+                &[chords[0], chords[1], "B4 E4 C4 A2"]
+            ),
+            [finding(
+                2,
+                &[Voice::Soprano],
+                "leading tone resolution",
+                "unresolved leading tone"
+            )]
+        );
+    }
+
+    #[test]
+    fn seventh_resolution() {
+        let exercise = "key=C major | G2 7 | C3";
+        assert_eq!(
+            check(SeventhResolution, exercise, &["F4 D4 B3 G2", "E4 C4 C4 C3"]),
+            []
+        );
+        assert_eq!(
+            check(SeventhResolution, exercise, &["F4 D4 B3 G2", "G4 E4 C4 C3"]),
+            [finding(
+                1,
+                &[Voice::Soprano],
+                "seventh resolution",
+                "unresolved seventh"
+            )]
+        );
+        // the seventh of a 4/2 chord is in the bass, which is given
+        assert_eq!(
+            check(
+                SeventhResolution,
+                "key=C major | F2 4/2 | G2",
+                &["D4 B3 G3 F2", "D4 B3 G3 G2"]
+            ),
+            []
+        );
+    }
+
+    #[test]
+    fn doubling() {
+        assert_eq!(
+            check(LeadingToneDoubling, "key=C major | G2", &["B4 D4 B3 G2"]),
+            [finding(
+                0,
+                &[Voice::Tenor, Voice::Soprano],
+                "leading tone doubling",
+                "doubled leading tone"
+            )]
+        );
+        assert_eq!(
+            check(ChromaticDoubling, "key=C major | E2 #", &["G#4 B3 G#3 E2"]),
+            [finding(
+                0,
+                &[Voice::Tenor, Voice::Soprano],
+                "chromatic doubling",
+                "doubled chromatic note"
+            )]
+        );
+        // a chromatic bass note counts as well
+        assert_eq!(
+            check(ChromaticDoubling, "key=C major | F#2 6", &["F#4 D4 A3 F#2"]),
+            [finding(
+                0,
+                &[Voice::Bass, Voice::Soprano],
+                "chromatic doubling",
+                "doubled chromatic note"
+            )]
+        );
+        assert_eq!(
+            check(PreferredDoubling, "key=C major | C3", &["E4 C4 E3 C3"]),
+            [finding(
+                0,
+                &[Voice::Tenor, Voice::Soprano],
+                "preferred doubling",
+                "unpreferred doubling"
+            )]
+        );
+        assert_eq!(
+            check(PreferredDoubling, "key=C major | C3", &["E4 C4 G3 C3"]),
+            []
+        );
+    }
+
+    #[test]
+    fn augmented_or_diminished_interval() {
+        // the rule goes by spelling, so f to g# is an augmented second
+        assert_eq!(
+            check(
+                AugmentedOrDiminishedInterval,
+                "key=A minor | D3 | E3 #",
+                &["F4 D4 A3 D3", "G#4 E4 B3 E3"]
+            ),
+            [finding(
+                1,
+                &[Voice::Soprano],
+                "augmented or diminished interval",
+                "augmented or diminished interval"
+            )]
+        );
+        // but f to ab is a minor third, though it is the same distance
+        assert_eq!(
+            check(
+                AugmentedOrDiminishedInterval,
+                "key=F minor | Db3 | C3",
+                &["F4 Db4 Ab3 Db3", "Ab4 C4 G3 C3"]
+            ),
+            []
+        );
+        // a chromatic semitone isn't a melodic interval to avoid
+        assert_eq!(
+            check(
+                AugmentedOrDiminishedInterval,
+                "key=D minor | A2 | A2 #",
+                &["E4 C4 A3 A2", "E4 C#4 A3 A2"]
+            ),
+            []
+        );
+        // the augmented fourth and diminished fifth in the tenor and alto
+        assert_eq!(
+            check(
+                AugmentedOrDiminishedInterval,
+                "key=C major | F2 | G2 7",
+                &["C5 B4 F3 F2", "D5 F4 B3 G2"]
+            ),
+            [
+                finding(
+                    1,
+                    &[Voice::Alto],
+                    "augmented or diminished interval",
+                    "augmented or diminished interval"
+                ),
+                finding(
+                    1,
+                    &[Voice::Tenor],
+                    "augmented or diminished interval",
+                    "augmented or diminished interval"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn large_leap() {
+        let exercise = "key=C major | C3 | C2";
+        assert_eq!(
+            check(LargeLeap, exercise, &["C4 G3 E3 C3", "E5 C5 E4 C2"]),
+            [
+                finding(
+                    1,
+                    &[Voice::Soprano],
+                    "large leap",
+                    "leap larger than an octave"
+                ),
+                finding(
+                    1,
+                    &[Voice::Alto],
+                    "large leap",
+                    "leap larger than an octave"
+                ),
+            ]
+        );
+        // an octave is allowed, and the bass is given by the exercise
+        assert_eq!(
+            check(LargeLeap, exercise, &["C4 G3 E3 C3", "C5 G4 E4 C2"]),
+            []
+        );
+    }
+
+    #[test]
+    fn leap_recovery() {
+        let exercise = "key=C major | C3 | F2 | G2";
+        assert_eq!(
+            check(
+                LeapRecovery,
+                exercise,
+                &["C5 G4 E4 C3", "F4 C4 A3 F2", "G4 D4 B3 G2"]
+            ),
+            []
+        );
+        assert_eq!(
+            check(
+                LeapRecovery,
+                exercise,
+                &["C5 G4 E4 C3", "F4 C4 A3 F2", "D4 B3 G3 G2"]
+            ),
+            [
+                finding(
+                    2,
+                    &[Voice::Soprano],
+                    "leap recovery",
+                    "leap not followed by a step back"
+                ),
+                finding(
+                    2,
+                    &[Voice::Alto],
+                    "leap recovery",
+                    "leap not followed by a step back"
+                ),
+                finding(
+                    2,
+                    &[Voice::Tenor],
+                    "leap recovery",
+                    "leap not followed by a step back"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn melodic_minor() {
+        let unraised_rising = ("key=A minor | D3 | C3", ["F4 D4 A3 D3", "G4 E4 C4 C3"]);
+        assert_eq!(
+            check_with(
+                MelodicMinor,
+                unraised_rising.0,
+                MinorScale::Melodic,
+                &unraised_rising.1
+            ),
+            [finding(
+                1,
+                &[Voice::Soprano],
+                "melodic minor",
+                "unraised sixth or seventh rising"
+            )]
+        );
+        assert_eq!(
+            check_with(
+                MelodicMinor,
+                "key=A minor | E3 | F3",
+                MinorScale::Melodic,
+                &["G#4 E4 B3 E3", "F4 C4 A3 F3"]
+            ),
+            [finding(
+                1,
+                &[Voice::Soprano],
+                "melodic minor",
+                "raised sixth or seventh falling"
+            )]
+        );
+        for (exercise, chords) in [
+            ("key=A minor | D3 | E3", ["F#4 D4 A3 D3", "G#4 E4 B3 E3"]),
+            ("key=A minor | E3 | D3", ["G4 E4 B3 E3", "F4 D4 A3 D3"]),
+        ] {
+            assert_eq!(
+                check_with(MelodicMinor, exercise, MinorScale::Melodic, &chords),
+                []
+            );
+        }
+        // the other forms of the minor scale leave it to the figures
+        for minor_scale in [MinorScale::Natural, MinorScale::Harmonic] {
+            assert_eq!(
+                check_with(
+                    MelodicMinor,
+                    unraised_rising.0,
+                    minor_scale,
+                    &unraised_rising.1
+                ),
+                []
+            );
+        }
+    }
+
+    #[test]
+    fn findings_show_their_rule() {
+        let unresolved = Finding {
+            points: -50,
+            ..finding(
+                11,
+                &[Voice::Soprano],
+                "leading tone resolution",
+                "unresolved leading tone",
+            )
+        };
+        assert_eq!(
+            unresolved.to_string(),
+            "chord 12, soprano [leading tone resolution]: unresolved leading tone (-50)"
+        );
+        let parallel = finding(
+            3,
+            &[Voice::Alto, Voice::Bass],
+            "parallel fifths",
+            "parallel fifths",
+        );
+        assert_eq!(
+            parallel.to_string(),
+            "chord 4, alto and bass [parallel fifths]: parallel fifths (+1)"
+        );
+    }
+}
@@ -0,0 +1,321 @@
+use crate::machine_learning::ChordInput;
+use crate::music_theory::*;
+use crate::rules::*;
+use std::fmt::Display;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Voice {
+    Soprano,
+    Alto,
+    Tenor,
+    Bass,
+}
+impl Voice {
+    pub fn note(self, chord: &Chord) -> NoteWithOctave {
+        match self {
+            Voice::Soprano => chord.s,
+            Voice::Alto => chord.a,
+            Voice::Tenor => chord.t,
+            Voice::Bass => chord.b,
+        }
+    }
+}
+impl Display for Voice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let result = match self {
+            Voice::Soprano => "soprano",
+            Voice::Alto => "alto",
+            Voice::Tenor => "tenor",
+            Voice::Bass => "bass",
+        };
+        write!(f, "{}", result)
+    }
+}
+
+// a penalty or bonus given by one of the rules, and where in the realisation it applies
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    // for rules about the movement between two chords, this is the second chord
+    pub chord_index: usize,
+    pub voices: &'static [Voice],
+    // the name of the rule, and what exactly it found
+    pub rule: &'static str,
+    pub description: &'static str,
+    pub points: i32,
+}
+impl Display for Finding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let voices: Vec<String> = self.voices.iter().map(|x| x.to_string()).collect();
+        write!(
+            f,
+            "chord {}, {} [{}]: {} ({:+})",
+            self.chord_index + 1,
+            voices.join(" and "),
+            self.rule,
+            self.description,
+            self.points
+        )
+    }
+}
+
+// every penalty and bonus that makes up the score of a realisation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub findings: Vec<Finding>,
+    pub score: i32,
+}
+impl Report {
+    pub fn penalties(&self) -> impl Iterator<Item = &Finding> {
+        self.findings.iter().filter(|x| x.points < 0)
+    }
+    pub fn bonuses(&self) -> impl Iterator<Item = &Finding> {
+        self.findings.iter().filter(|x| x.points > 0)
+    }
+}
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for finding in self.findings.iter() {
+            writeln!(f, "{finding}")?;
+        }
+        write!(f, "score: {}", self.score)
+    }
+}
+
+// what a rule gets to look at when it checks a chord
+pub struct Context<'a> {
+    pub chords_input: &'a [ChordInput],
+    pub chord_index: usize,
+    // the chords the rule looks at, oldest first and ending with the one at `chord_index`
+    pub chords: &'a [Chord],
+}
+impl Context<'_> {
+    pub fn chord(&self) -> &Chord {
+        &self.chords[self.chords.len() - 1]
+    }
+    // only for rules that look at more than one chord
+    pub fn prev_chord(&self) -> &Chord {
+        &self.chords[self.chords.len() - 2]
+    }
+    pub fn input(&self) -> &ChordInput {
+        &self.chords_input[self.chord_index]
+    }
+    pub fn prev_input(&self) -> &ChordInput {
+        &self.chords_input[self.chord_index - 1]
+    }
+}
+
+// one of the rules of part writing, which `Scorer` gives points for breaking (or following)
+pub trait Rule: Send + Sync {
+    // the name the rule goes by in a `Scorer`
+    fn name(&self) -> &'static str;
+    // how many chords in a row the rule looks at: 1 for rules about a single chord, 2 for rules
+    // about moving from one chord to the next, or 3. the exact solver can't handle any more.
+    fn span(&self) -> usize;
+    // calls `found` for every place the last of `context.chords` breaks the rule, with the
+    // voices involved, a description, and how many times over to apply the rule's weight
+    fn check(&self, context: &Context, found: &mut dyn FnMut(&'static [Voice], &'static str, i32));
+}
+
+// the rules used to score a realisation and their weights, where penalties are negative and a
+// weight of 0 turns the rule off
+#[derive(Clone)]
+pub struct Scorer {
+    rules: Vec<(Arc<dyn Rule>, i32)>,
+}
+impl Default for Scorer {
+    fn default() -> Self {
+        let mut scorer = Self::empty();
+        scorer.add(AdjacentUnisons, -25);
+        scorer.add(ComfortableRange, -5);
+        scorer.add(MelodicMotion, 5);
+        scorer.add(ParallelFifths, -100);
+        scorer.add(ParallelOctaves, -100);
+        scorer.add(ContraryFifthsAndOctaves, -50);
+        scorer.add(HiddenFifthsAndOctaves, -50);
+        scorer.add(VoiceCrossing, -50);
+        scorer.add(VoiceOverlap, -25);
+        scorer.add(Spacing::default(), -30);
+        scorer.add(LeadingToneResolution, -50);
+        scorer.add(SeventhResolution, -50);
+        scorer.add(AugmentedOrDiminishedInterval, -50);
+        scorer.add(LargeLeap, -50);
+        scorer.add(LeapRecovery, -30);
+        scorer.add(MelodicMinor, -30);
+        scorer.add(LeadingToneDoubling, -50);
+        scorer.add(ChromaticDoubling, -50);
+        scorer.add(PreferredDoubling, -15);
+        scorer
+    }
+}
+impl std::fmt::Debug for Scorer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(
+                self.rules
+                    .iter()
+                    .map(|(rule, weight)| (rule.name(), weight)),
+            )
+            .finish()
+    }
+}
+impl Scorer {
+    // a scorer without any rules, which gives every realisation a score of 0
+    pub fn empty() -> Self {
+        Self { rules: Vec::new() }
+    }
+    // adds a rule, replacing any rule with the same name
+    pub fn add(&mut self, rule: impl Rule + 'static, weight: i32) {
+        assert!(
+            (1..=3).contains(&rule.span()),
+            "rules can only look at 1 to 3 chords"
+        );
+        match self.rules.iter().position(|x| x.0.name() == rule.name()) {
+            Some(i) => self.rules[i] = (Arc::new(rule), weight),
+            None => self.rules.push((Arc::new(rule), weight)),
+        }
+    }
+    // removes a rule, returning whether there was one with that name
+    pub fn remove(&mut self, name: &str) -> bool {
+        let len = self.rules.len();
+        self.rules.retain(|x| x.0.name() != name);
+        self.rules.len() != len
+    }
+    pub fn weight(&self, name: &str) -> Option<i32> {
+        self.rules.iter().find(|x| x.0.name() == name).map(|x| x.1)
+    }
+    // changes the weight of a rule, returning whether there was one with that name
+    pub fn set_weight(&mut self, name: &str, weight: i32) -> bool {
+        match self.rules.iter_mut().find(|x| x.0.name() == name) {
+            Some(rule) => {
+                rule.1 = weight;
+                true
+            }
+            None => false,
+        }
+    }
+    // the rules in the order they were added, along with their weights
+    pub fn rules(&self) -> impl Iterator<Item = (&dyn Rule, i32)> {
+        self.rules
+            .iter()
+            .map(|(rule, weight)| (rule.as_ref(), *weight))
+    }
+
+    // the part of the score that only depends on a single chord
+    pub fn chord_score(
+        &self,
+        chords_input: &[ChordInput],
+        chord_index: usize,
+        chord: &Chord,
+    ) -> i32 {
+        let mut score = 0;
+        self.check(
+            chords_input,
+            chord_index,
+            std::slice::from_ref(chord),
+            &mut |finding| score += finding.points,
+        );
+        score
+    }
+
+    // the part of the score that depends on the movement into the chord at `chord_index` from
+    // the one before it
+    pub fn transition_score(
+        &self,
+        chords_input: &[ChordInput],
+        chord_index: usize,
+        prev_chord: &Chord,
+        chord: &Chord,
+    ) -> i32 {
+        let mut score = 0;
+        self.check(
+            chords_input,
+            chord_index,
+            &[*prev_chord, *chord],
+            &mut |finding| score += finding.points,
+        );
+        score
+    }
+
+    // the part of the score that depends on the three chords up to and including the one at
+    // `chord_index`
+    pub fn three_chord_score(
+        &self,
+        chords_input: &[ChordInput],
+        chord_index: usize,
+        chords: [&Chord; 3],
+    ) -> i32 {
+        let mut score = 0;
+        self.check(
+            chords_input,
+            chord_index,
+            &chords.map(|x| *x),
+            &mut |finding| score += finding.points,
+        );
+        score
+    }
+
+    // the score never looks at more than three chords in a row, which is what lets
+    // `dynamic_programming` find the best realisation exactly
+    pub fn score(&self, chords_input: &[ChordInput], chords: &[Chord]) -> i32 {
+        let mut score = 0;
+        for finding in self.findings(chords_input, chords) {
+            score += finding.points;
+        }
+        score
+    }
+
+    // every penalty and bonus given by the rules, in the order of the chords
+    pub fn findings(&self, chords_input: &[ChordInput], chords: &[Chord]) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        for i in 0..chords.len() {
+            for span in (1..=3).rev() {
+                if i + 1 >= span {
+                    self.check(chords_input, i, &chords[i + 1 - span..=i], &mut |finding| {
+                        findings.push(finding)
+                    });
+                }
+            }
+        }
+        findings
+    }
+
+    // the findings along with the score they add up to
+    pub fn report(&self, chords_input: &[ChordInput], chords: &[Chord]) -> Report {
+        let findings = self.findings(chords_input, chords);
+        let score = findings.iter().map(|x| x.points).sum();
+        Report { findings, score }
+    }
+
+    // runs the rules that look at exactly as many chords as there are in `chords`
+    fn check(
+        &self,
+        chords_input: &[ChordInput],
+        chord_index: usize,
+        chords: &[Chord],
+        found: &mut impl FnMut(Finding),
+    ) {
+        let context = Context {
+            chords_input,
+            chord_index,
+            chords,
+        };
+        for (rule, weight) in self.rules.iter() {
+            if *weight == 0 || rule.span() != chords.len() {
+                continue;
+            }
+            rule.check(&context, &mut |voices, description, times| {
+                let points = weight * times;
+                if points != 0 {
+                    found(Finding {
+                        chord_index,
+                        voices,
+                        rule: rule.name(),
+                        description,
+                        points,
+                    })
+                }
+            });
+        }
+    }
+}
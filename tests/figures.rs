@@ -0,0 +1,46 @@
+use figured_bass::{parse_exercise, Accidental};
+
+#[test]
+fn shorthand_figures_are_expanded() {
+    let sharp = Some(Accidental::Sharp);
+    let flat = Some(Accidental::Flat);
+    for (figure, expanded) in [
+        ("", vec![(3, None), (5, None)]),
+        ("6", vec![(3, None), (6, None)]),
+        ("6/4", vec![(4, None), (6, None)]),
+        ("7", vec![(3, None), (5, None), (7, None)]),
+        ("6/5", vec![(3, None), (5, None), (6, None)]),
+        ("4/3", vec![(3, None), (4, None), (6, None)]),
+        ("4/2", vec![(2, None), (4, None), (6, None)]),
+        ("2", vec![(2, None), (4, None), (6, None)]),
+        // a lone accidental is an altered third
+        ("#", vec![(3, sharp), (5, None)]),
+        ("b", vec![(3, flat), (5, None)]),
+        // accidentals on the figures are kept
+        ("6#", vec![(3, None), (6, sharp)]),
+        ("7/#", vec![(3, sharp), (5, None), (7, None)]),
+        ("6/5/b3", vec![(3, flat), (5, None), (6, None)]),
+        // figures that are already complete are left alone
+        ("6/4/3", vec![(3, None), (4, None), (6, None)]),
+        ("9/7", vec![(7, None), (9, None)]),
+    ] {
+        let (_, figures) = parse_exercise(&format!("key=C major | D3 {figure}")).unwrap();
+        assert_eq!(figures[0].expanded_figures(), expanded, "`{figure}`");
+    }
+}
+
+#[test]
+fn lone_accidentals() {
+    for (figure, lone) in [
+        ("#", Some(Accidental::Sharp)),
+        ("n", Some(Accidental::Natural)),
+        ("#3", Some(Accidental::Sharp)),
+        ("", None),
+        ("3", None),
+        ("6#", None),
+        ("#/6", None),
+    ] {
+        let (_, figures) = parse_exercise(&format!("key=C major | D3 {figure}")).unwrap();
+        assert_eq!(figures[0].lone_accidental(), lone, "`{figure}`");
+    }
+}
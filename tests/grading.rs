@@ -0,0 +1,160 @@
+use figured_bass::grading::grading_scorer;
+use figured_bass::{
+    grade, parse_exercise, Accidental, Chord, GradeError, Grading, MinorScale, Mistake, Note,
+    NoteName, NoteWithOctave, Voice,
+};
+
+// a note like "F#4"
+fn note(note: &str) -> NoteWithOctave {
+    let name = NoteName::try_from(&note[..1]).unwrap();
+    let (accidental, octave) = match &note[1..2] {
+        "#" => (Some(Accidental::Sharp), &note[2..]),
+        "b" => (Some(Accidental::Flat), &note[2..]),
+        _ => (None, &note[1..]),
+    };
+    NoteWithOctave::new(name, accidental, octave.parse().unwrap())
+}
+
+// a chord from its notes from the soprano down, e.g. "G4 D4 B3 G2"
+fn chord(notes: &str) -> Chord {
+    let notes: Vec<NoteWithOctave> = notes.split(' ').map(note).collect();
+    Chord::new(notes[0], notes[1], notes[2], notes[3])
+}
+
+fn grade_chords(
+    exercise: &str,
+    minor_scale: MinorScale,
+    chords: &[&str],
+) -> Result<Grading, GradeError> {
+    let (key_sig, figures) = parse_exercise(exercise).unwrap();
+    let chords: Vec<Chord> = chords.iter().map(|x| chord(x)).collect();
+    grade(key_sig, &figures, &chords, minor_scale, &grading_scorer())
+}
+
+const EXERCISE: &str = "key=C major | C3 | F3 | G3 | C3";
+const CORRECT: [&str; 4] = ["G4 E4 C4 C3", "A4 F4 C4 F3", "G4 D4 B3 G3", "G4 E4 C4 C3"];
+
+#[test]
+fn a_correct_realisation_has_no_mistakes() {
+    let grading = grade_chords(EXERCISE, MinorScale::Natural, &CORRECT).unwrap();
+    assert_eq!(grading.mistakes, []);
+    assert_eq!(grading.report.penalties().count(), 0);
+    assert_eq!((grading.correct_chords, grading.total_chords), (4, 4));
+    assert_eq!(grading.percentage(), 100);
+    assert_eq!(grading.to_string(), "4/4 chords correct (100%)");
+}
+
+#[test]
+fn wrong_bass() {
+    let mut chords = CORRECT;
+    chords[1] = "A4 F4 C4 F2";
+    let grading = grade_chords(EXERCISE, MinorScale::Natural, &chords).unwrap();
+    assert_eq!(
+        grading.mistakes,
+        [Mistake::WrongBass {
+            chord_index: 1,
+            expected: note("F3"),
+            found: note("F2"),
+        }]
+    );
+    assert_eq!(grading.correct_chords, 3);
+    assert!(grading
+        .to_string()
+        .starts_with("chord 2, bass: F2 should be F3\n"));
+}
+
+#[test]
+fn wrong_and_missing_notes() {
+    let mut chords = CORRECT;
+    // a d instead of the c, and no g in the first chord
+    chords[0] = "E4 C4 C4 C3";
+    chords[1] = "A4 F4 D4 F3";
+    let grading = grade_chords(EXERCISE, MinorScale::Natural, &chords).unwrap();
+    assert_eq!(
+        grading.mistakes,
+        [
+            Mistake::MissingNote {
+                chord_index: 0,
+                note: Note::new(NoteName::G, None),
+            },
+            Mistake::WrongNote {
+                chord_index: 1,
+                voice: Voice::Tenor,
+                note: note("D4"),
+            },
+            Mistake::MissingNote {
+                chord_index: 1,
+                note: Note::new(NoteName::C, None),
+            },
+        ]
+    );
+    assert_eq!((grading.correct_chords, grading.total_chords), (2, 4));
+    assert_eq!(grading.percentage(), 50);
+    let text = grading.to_string();
+    assert!(text.contains("chord 1: missing G\n"));
+    assert!(text.contains("chord 2, tenor: D4 isn't in the chord\n"));
+    assert!(text.ends_with("2/4 chords correct (50%)"));
+}
+
+#[test]
+fn penalties_make_a_chord_incorrect() {
+    let mut chords = CORRECT;
+    // parallel fifths and octaves from the second chord to the third
+    chords[2] = "B4 G4 D4 G3";
+    chords[3] = "C5 G4 E4 C3";
+    let grading = grade_chords(EXERCISE, MinorScale::Natural, &chords).unwrap();
+    assert_eq!(grading.mistakes, []);
+    let penalised: Vec<usize> = grading.report.penalties().map(|x| x.chord_index).collect();
+    assert!(!penalised.is_empty());
+    assert!(penalised.iter().all(|x| *x == 2));
+    assert_eq!(grading.correct_chords, 3);
+    assert_eq!(grading.percentage(), 75);
+}
+
+#[test]
+fn the_number_of_chords_has_to_match() {
+    assert_eq!(
+        grade_chords(EXERCISE, MinorScale::Natural, &CORRECT[..3]),
+        Err(GradeError::WrongNumberOfChords {
+            figures: 4,
+            chords: 3
+        })
+    );
+    assert_eq!(
+        grade_chords(EXERCISE, MinorScale::Natural, &[]),
+        Err(GradeError::WrongNumberOfChords {
+            figures: 4,
+            chords: 0
+        })
+    );
+}
+
+#[test]
+fn the_sixth_and_seventh_depend_on_the_minor_scale() {
+    let exercise = "key=A minor | A2 | E3 | A2";
+    let chords = ["C5 E4 A3 A2", "B4 E4 G#3 E3", "A4 E4 C4 A2"];
+    let grading = grade_chords(exercise, MinorScale::Natural, &chords).unwrap();
+    assert_eq!(
+        grading.mistakes,
+        [
+            Mistake::WrongNote {
+                chord_index: 1,
+                voice: Voice::Tenor,
+                note: note("G#3"),
+            },
+            Mistake::MissingNote {
+                chord_index: 1,
+                note: Note::new(NoteName::G, None),
+            },
+        ]
+    );
+    for minor_scale in [MinorScale::Harmonic, MinorScale::Melodic] {
+        let grading = grade_chords(exercise, minor_scale, &chords).unwrap();
+        assert_eq!(grading.mistakes, [], "{minor_scale:?}");
+    }
+    // with the melodic minor scale an upper voice can have either form, and it is up to the scorer
+    // whether it went the right way
+    let chords = ["C5 E4 A3 A2", "B4 E4 G3 E3", "A4 E4 C4 A2"];
+    let grading = grade_chords(exercise, MinorScale::Melodic, &chords).unwrap();
+    assert_eq!(grading.mistakes, []);
+}
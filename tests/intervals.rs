@@ -0,0 +1,126 @@
+use figured_bass::{Accidental, Interval, NoteName, NoteWithOctave, Quality};
+
+// a note like "F#4" or "Cbb3"
+fn note(note: &str) -> NoteWithOctave {
+    let name = NoteName::try_from(&note[..1]).unwrap();
+    let split = note.find(|c: char| c.is_ascii_digit()).unwrap();
+    let accidental = match &note[1..split] {
+        "" => None,
+        "#" => Some(Accidental::Sharp),
+        "##" => Some(Accidental::DoubleSharp),
+        "b" => Some(Accidental::Flat),
+        "bb" => Some(Accidental::DoubleFlat),
+        other => panic!("unexpected accidental {other}"),
+    };
+    NoteWithOctave::new(name, accidental, note[split..].parse().unwrap())
+}
+
+fn interval(quality: Quality, number: u32, octaves: u32) -> Interval {
+    Interval::new(quality, number, octaves).unwrap()
+}
+
+#[test]
+fn intervals_between_spelt_notes() {
+    for (a, b, expected) in [
+        ("C4", "E4", "M3"),
+        ("C4", "Fb4", "d4"),
+        ("D4", "Ab4", "d5"),
+        ("F3", "B3", "A4"),
+        ("C4", "C4", "P1"),
+        ("C4", "C#4", "A1"),
+        ("C4", "C5", "P8"),
+        ("C3", "E4", "M10"),
+        ("G2", "G4", "P15"),
+        ("E4", "F4", "m2"),
+        ("F4", "G#4", "A2"),
+        ("F4", "Ab4", "m3"),
+        // the order goes by letter, so these are seconds even though they sound lower
+        ("C4", "Dbb4", "d2"),
+        ("B#3", "Cb4", "dd2"),
+    ] {
+        assert_eq!(
+            Interval::between(note(a), note(b)).to_string(),
+            expected,
+            "{a} {b}"
+        );
+        assert_eq!(
+            Interval::between(note(b), note(a)).to_string(),
+            expected,
+            "{b} {a}"
+        );
+    }
+}
+
+#[test]
+fn qualities_have_to_go_with_the_number() {
+    assert_eq!(Interval::new(Quality::Perfect, 3, 0), None);
+    assert_eq!(Interval::new(Quality::Major, 5, 0), None);
+    assert_eq!(Interval::new(Quality::Minor, 1, 1), None);
+    assert_eq!(Interval::new(Quality::Augmented(0), 4, 0), None);
+    assert_eq!(Interval::new(Quality::Major, 8, 0), None);
+    assert!(Interval::new(Quality::Diminished(2), 3, 1).is_some());
+}
+
+#[test]
+fn semitones_and_compound_numbers() {
+    for (interval, semitones, number) in [
+        (interval(Quality::Major, 3, 0), 4, 3),
+        (interval(Quality::Diminished(1), 4, 0), 4, 4),
+        (interval(Quality::Augmented(1), 4, 0), 6, 4),
+        (interval(Quality::Diminished(1), 5, 0), 6, 5),
+        (interval(Quality::Diminished(1), 7, 0), 9, 7),
+        (interval(Quality::Perfect, 1, 1), 12, 8),
+        (interval(Quality::Major, 3, 1), 16, 10),
+        (interval(Quality::Diminished(2), 2, 0), -1, 2),
+    ] {
+        assert_eq!(interval.semitones(), semitones, "{interval}");
+        assert_eq!(interval.compound_number(), number, "{interval}");
+    }
+}
+
+#[test]
+fn adding_and_subtracting_intervals() {
+    for (from, interval, to) in [
+        ("D4", interval(Quality::Major, 3, 0), "F#4"),
+        ("C4", interval(Quality::Augmented(1), 4, 0), "F#4"),
+        ("E4", interval(Quality::Diminished(1), 5, 0), "Bb4"),
+        ("B3", interval(Quality::Minor, 2, 0), "C4"),
+        ("B3", interval(Quality::Major, 2, 0), "C#4"),
+        ("C4", interval(Quality::Perfect, 1, 1), "C5"),
+        ("Eb3", interval(Quality::Major, 3, 1), "G4"),
+        ("C#4", interval(Quality::Diminished(1), 7, 0), "Bb4"),
+        ("F4", interval(Quality::Augmented(1), 2, 0), "G#4"),
+    ] {
+        assert_eq!(note(from) + interval, note(to), "{from} + {interval}");
+        assert_eq!(note(to) - interval, note(from), "{to} - {interval}");
+        assert_eq!(Interval::between(note(from), note(to)), interval);
+    }
+}
+
+#[test]
+fn inversions_and_simple_intervals() {
+    for (interval, inverted) in [
+        (interval(Quality::Major, 3, 0), "m6"),
+        (interval(Quality::Augmented(1), 4, 0), "d5"),
+        (interval(Quality::Diminished(2), 7, 0), "AA2"),
+        (interval(Quality::Perfect, 5, 0), "P4"),
+        (interval(Quality::Major, 3, 1), "m6"),
+        // a unison inverts to an octave and the other way round
+        (interval(Quality::Perfect, 1, 0), "P8"),
+        (interval(Quality::Perfect, 1, 1), "P1"),
+        (interval(Quality::Augmented(1), 1, 0), "d8"),
+    ] {
+        assert_eq!(interval.inverted().to_string(), inverted, "{interval}");
+    }
+    for (interval, simple) in [
+        (interval(Quality::Major, 3, 1), "M3"),
+        (interval(Quality::Minor, 7, 2), "m7"),
+        (interval(Quality::Perfect, 5, 0), "P5"),
+        // an octave stays an octave, however many there are
+        (interval(Quality::Perfect, 1, 1), "P8"),
+        (interval(Quality::Perfect, 1, 2), "P8"),
+        (interval(Quality::Perfect, 1, 0), "P1"),
+    ] {
+        assert_eq!(interval.simple().to_string(), simple, "{interval}");
+    }
+}
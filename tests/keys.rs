@@ -0,0 +1,299 @@
+use figured_bass::{
+    parse_exercise, Accidental, KeySignature, MinorScale, Note, NoteName, Tonality,
+};
+
+// every major key from seven flats to seven sharps, with the scale it should have
+const MAJOR_KEYS: [(&str, &str); 15] = [
+    ("Cb", "Cb Db Eb Fb Gb Ab Bb"),
+    ("Gb", "Gb Ab Bb Cb Db Eb F"),
+    ("Db", "Db Eb F Gb Ab Bb C"),
+    ("Ab", "Ab Bb C Db Eb F G"),
+    ("Eb", "Eb F G Ab Bb C D"),
+    ("Bb", "Bb C D Eb F G A"),
+    ("F", "F G A Bb C D E"),
+    ("C", "C D E F G A B"),
+    ("G", "G A B C D E F#"),
+    ("D", "D E F# G A B C#"),
+    ("A", "A B C# D E F# G#"),
+    ("E", "E F# G# A B C# D#"),
+    ("B", "B C# D# E F# G# A#"),
+    ("F#", "F# G# A# B C# D# E#"),
+    ("C#", "C# D# E# F# G# A# B#"),
+];
+
+// the same for the minor keys (as natural minor scales)
+const MINOR_KEYS: [(&str, &str); 15] = [
+    ("Ab", "Ab Bb Cb Db Eb Fb Gb"),
+    ("Eb", "Eb F Gb Ab Bb Cb Db"),
+    ("Bb", "Bb C Db Eb F Gb Ab"),
+    ("F", "F G Ab Bb C Db Eb"),
+    ("C", "C D Eb F G Ab Bb"),
+    ("G", "G A Bb C D Eb F"),
+    ("D", "D E F G A Bb C"),
+    ("A", "A B C D E F G"),
+    ("E", "E F# G A B C D"),
+    ("B", "B C# D E F# G A"),
+    ("F#", "F# G# A B C# D E"),
+    ("C#", "C# D# E F# G# A B"),
+    ("G#", "G# A# B C# D# E F#"),
+    ("D#", "D# E# F# G# A# B C#"),
+    ("A#", "A# B# C# D# E# F# G#"),
+];
+
+fn all_keys() -> impl Iterator<Item = (i32, Tonality, &'static str, &'static str)> {
+    let major = MAJOR_KEYS
+        .iter()
+        .enumerate()
+        .map(|(i, (tonic, scale))| (i as i32 - 7, Tonality::Major, *tonic, *scale));
+    let minor = MINOR_KEYS
+        .iter()
+        .enumerate()
+        .map(|(i, (tonic, scale))| (i as i32 - 7, Tonality::Minor, *tonic, *scale));
+    major.chain(minor)
+}
+
+fn note(name: &str) -> Note {
+    let letter = NoteName::try_from(&name[..1]).unwrap();
+    let accidental = match &name[1..] {
+        "" => None,
+        "#" => Some(Accidental::Sharp),
+        "b" => Some(Accidental::Flat),
+        other => panic!("unexpected accidental {other}"),
+    };
+    Note::new(letter, accidental)
+}
+
+fn scale_string(key_sig: KeySignature) -> String {
+    let notes: Vec<String> = key_sig.get_scale().iter().map(|x| x.to_string()).collect();
+    notes.join(" ")
+}
+
+#[test]
+fn every_key_signature_has_the_right_tonic_and_scale() {
+    for (fifths, tonality, tonic, scale) in all_keys() {
+        let key_sig = KeySignature::from_fifths(fifths, tonality).unwrap();
+        assert_eq!(key_sig.fifths(), fifths);
+        assert_eq!(
+            key_sig.get_starting_note(),
+            note(tonic),
+            "{fifths} fifths, {tonality:?}"
+        );
+        assert_eq!(scale_string(key_sig), scale, "{tonic} {tonality:?}");
+    }
+}
+
+#[test]
+fn every_tonic_finds_its_key_signature() {
+    for (fifths, tonality, tonic, _) in all_keys() {
+        let tonic = note(tonic);
+        let key_sig = KeySignature::from_note(tonic.name, tonic.accidental, tonality).unwrap();
+        assert_eq!(key_sig.fifths(), fifths, "{tonic} {tonality:?}");
+        assert_eq!(key_sig.tonality, tonality);
+    }
+}
+
+#[test]
+fn scales_use_every_letter_once_and_only_the_key_signature_accidentals() {
+    for (fifths, tonality, tonic, _) in all_keys() {
+        let key_sig = KeySignature::from_fifths(fifths, tonality).unwrap();
+        let scale = key_sig.get_scale();
+        let mut letters: Vec<&str> = scale.iter().map(|x| x.name.into()).collect();
+        letters.sort();
+        letters.dedup();
+        assert_eq!(letters.len(), 7, "{tonic} {tonality:?}");
+
+        let expected_accidental = if fifths < 0 {
+            Accidental::Flat
+        } else {
+            Accidental::Sharp
+        };
+        let altered = scale.iter().filter(|x| x.accidental.is_some()).count();
+        assert_eq!(
+            altered,
+            fifths.unsigned_abs() as usize,
+            "{tonic} {tonality:?}"
+        );
+        assert!(scale
+            .iter()
+            .all(|x| x.accidental.is_none() || x.accidental == Some(expected_accidental)));
+    }
+}
+
+#[test]
+fn relative_keys_share_their_notes() {
+    for fifths in -7..=7 {
+        let major = KeySignature::from_fifths(fifths, Tonality::Major).unwrap();
+        let minor = KeySignature::from_fifths(fifths, Tonality::Minor).unwrap();
+        let mut major_scale = major.get_scale();
+        major_scale.rotate_left(5);
+        assert_eq!(major_scale, minor.get_scale(), "{fifths} fifths");
+    }
+}
+
+#[test]
+fn leading_tones_are_a_semitone_below_the_tonic() {
+    for (fifths, tonality, _, _) in all_keys() {
+        let key_sig = KeySignature::from_fifths(fifths, tonality).unwrap();
+        let leading_tone = key_sig.leading_tone();
+        let tonic = key_sig.get_starting_note();
+        assert_eq!((leading_tone.to_semitones() + 1) % 12, tonic.to_semitones());
+        assert_eq!(leading_tone.name, key_sig.get_scale()[6].name, "{tonic}");
+    }
+}
+
+#[test]
+fn keys_outside_seven_sharps_or_flats_are_rejected() {
+    assert_eq!(KeySignature::from_fifths(8, Tonality::Major), None);
+    assert_eq!(KeySignature::from_fifths(-8, Tonality::Minor), None);
+    // these would need eight or more sharps or flats
+    for (name, accidental, tonality) in [
+        (NoteName::G, Some(Accidental::Sharp), Tonality::Major),
+        (NoteName::F, Some(Accidental::Flat), Tonality::Major),
+        (NoteName::D, Some(Accidental::Flat), Tonality::Minor),
+        (NoteName::E, Some(Accidental::Sharp), Tonality::Minor),
+    ] {
+        assert_eq!(KeySignature::from_note(name, accidental, tonality), None);
+    }
+}
+
+#[test]
+fn keys_without_sharps_or_flats() {
+    let key_sig = KeySignature::from_fifths(0, Tonality::Minor).unwrap();
+    assert_eq!(key_sig.fifths(), 0);
+    assert_eq!(key_sig.get_starting_note(), note("A"));
+    assert_eq!(scale_string(key_sig), "A B C D E F G");
+    // however the key was found, there is only one key with no sharps or flats for each tonality
+    let (parsed, _) = parse_exercise("key=A minor | A2").unwrap();
+    assert_eq!(parsed, key_sig);
+    assert_eq!(
+        KeySignature::from_note(NoteName::A, None, Tonality::Minor),
+        Some(key_sig)
+    );
+    assert_ne!(KeySignature::from_fifths(0, Tonality::Major), Some(key_sig));
+}
+
+#[test]
+fn every_key_can_be_parsed_and_realised_as_triads() {
+    for (_, tonality, tonic, scale) in all_keys() {
+        let tonality_name: &str = tonality.into();
+        let scale: Vec<&str> = scale.split(' ').collect();
+        let exercise = format!(
+            "key={tonic} {tonality_name} | {}3 | {}3 6",
+            scale[0], scale[4]
+        );
+        let (key_sig, figures) = parse_exercise(&exercise).unwrap();
+
+        let tonic_triad: Vec<String> = figures[0]
+            .to_notes(key_sig)
+            .iter()
+            .map(|x| x.to_string())
+            .collect();
+        assert_eq!(tonic_triad, [scale[0], scale[2], scale[4]]);
+        let first_inversion: Vec<String> = figures[1]
+            .to_notes(key_sig)
+            .iter()
+            .map(|x| x.to_string())
+            .collect();
+        assert_eq!(first_inversion, [scale[4], scale[6], scale[2]]);
+    }
+}
+
+#[test]
+fn minor_scale_forms_raise_the_sixth_and_seventh() {
+    for fifths in -7..=7 {
+        let key_sig = KeySignature::from_fifths(fifths, Tonality::Minor).unwrap();
+        let natural = key_sig.get_scale();
+        assert_eq!(key_sig.get_scale_for(MinorScale::Natural, true), natural);
+        assert_eq!(key_sig.get_scale_for(MinorScale::Melodic, false), natural);
+
+        let harmonic = key_sig.get_scale_for(MinorScale::Harmonic, true);
+        assert_eq!(harmonic[..6], natural[..6]);
+        assert_eq!(harmonic[6], key_sig.leading_tone());
+
+        let melodic = key_sig.get_scale_for(MinorScale::Melodic, true);
+        assert_eq!(melodic[..5], natural[..5]);
+        assert_eq!(melodic[5], natural[5].altered(1));
+        assert_eq!(melodic[6], key_sig.leading_tone());
+
+        // the major key with the same tonic has the same notes as the rising melodic minor, apart
+        // from the third
+        let tonic = key_sig.get_starting_note();
+        let major = KeySignature::from_note(tonic.name, tonic.accidental, Tonality::Major);
+        if let Some(major) = major {
+            let mut major_scale = major.get_scale();
+            major_scale[2] = melodic[2];
+            assert_eq!(major_scale, melodic, "{tonic} minor");
+        }
+    }
+}
+
+const MODES: [Tonality; 7] = [
+    Tonality::Major,
+    Tonality::Dorian,
+    Tonality::Phrygian,
+    Tonality::Lydian,
+    Tonality::Mixolydian,
+    Tonality::Minor,
+    Tonality::Locrian,
+];
+
+#[test]
+fn modes_start_on_each_degree_of_the_major_scale_with_the_same_signature() {
+    for fifths in -7..=7 {
+        let major = KeySignature::from_fifths(fifths, Tonality::Major)
+            .unwrap()
+            .get_scale();
+        for (degree, tonality) in MODES.iter().enumerate() {
+            let key_sig = KeySignature::from_fifths(fifths, *tonality).unwrap();
+            let mut expected = major.clone();
+            expected.rotate_left(degree);
+            assert_eq!(
+                key_sig.get_scale(),
+                expected,
+                "{fifths} fifths, {tonality:?}"
+            );
+
+            let tonic = key_sig.get_starting_note();
+            let found = KeySignature::from_note(tonic.name, tonic.accidental, *tonality);
+            assert_eq!(found, Some(key_sig), "{tonic} {tonality:?}");
+        }
+    }
+}
+
+#[test]
+fn modes_on_white_notes() {
+    for (key, scale) in [
+        ("C ionian", "C D E F G A B"),
+        ("D dorian", "D E F G A B C"),
+        ("E phrygian", "E F G A B C D"),
+        ("F lydian", "F G A B C D E"),
+        ("G mixolydian", "G A B C D E F"),
+        ("A aeolian", "A B C D E F G"),
+        ("B locrian", "B C D E F G A"),
+        ("G dorian", "G A Bb C D E F"),
+        ("A mixolydian", "A B C# D E F# G"),
+        ("F# phrygian", "F# G A B C# D E"),
+        ("Bb lydian", "Bb C D E F G A"),
+    ] {
+        let (key_sig, _) = parse_exercise(&format!("key={key} | C3")).unwrap();
+        assert_eq!(scale_string(key_sig), scale, "{key}");
+    }
+}
+
+#[test]
+fn figures_are_diatonic_to_the_mode() {
+    // in d dorian the chord on g is major, and a 6 over e gives c natural
+    let (key_sig, figures) = parse_exercise("key=D dorian | G2 | E3 6 | C#3 6").unwrap();
+    let notes: Vec<Vec<String>> = figures
+        .iter()
+        .map(|figure| {
+            figure
+                .to_notes(key_sig)
+                .iter()
+                .map(|x| x.to_string())
+                .collect()
+        })
+        .collect();
+    assert_eq!(notes, [["G", "B", "D"], ["E", "G", "C"], ["C#", "E", "A"]]);
+    assert_eq!(key_sig.leading_tone(), note("C#"));
+}
@@ -0,0 +1,43 @@
+use figured_bass::lilypond::to_lilypond;
+use figured_bass::{parse_exercise, Chord, NoteName, NoteWithOctave, Realisation};
+
+fn note(name: NoteName, octave: u32) -> NoteWithOctave {
+    NoteWithOctave::new(name, None, octave)
+}
+
+#[test]
+fn writes_the_voices_and_figures() {
+    let (key_sig, figures) = parse_exercise("key=G major | G2 | B2 6 | C3 6/4").unwrap();
+    let realisation = Realisation {
+        chords: vec![
+            Chord::new(
+                note(NoteName::G, 4),
+                note(NoteName::D, 4),
+                note(NoteName::B, 3),
+                note(NoteName::G, 2),
+            ),
+            Chord::new(
+                note(NoteName::G, 4),
+                note(NoteName::D, 4),
+                note(NoteName::G, 3),
+                note(NoteName::B, 2),
+            ),
+            Chord::new(
+                note(NoteName::G, 4),
+                note(NoteName::E, 4),
+                note(NoteName::C, 4),
+                note(NoteName::C, 3),
+            ),
+        ],
+        score: 0,
+    };
+    let ly = to_lilypond(&realisation, &figures, key_sig);
+
+    assert!(ly.contains("  \\key g \\major\n"));
+    assert!(ly.contains("soprano = {\n  \\global\n  g'4 g'4 g'4 r4 \\bar \"|.\"\n}"));
+    assert!(ly.contains("bass = {\n  \\global\n  g,4 b,4 c4 r4 \\bar \"|.\"\n}"));
+    assert!(ly.contains("figuredBass = \\figuremode {\n  <_>4 <6>4 <6 4>4\n}"));
+    assert!(ly.contains("    \\new FiguredBass \\figuredBass\n"));
+    // `\figures` is a keyword, so lilypond would never look it up as a variable
+    assert!(!ly.contains("\\figures"));
+}
@@ -0,0 +1,137 @@
+use figured_bass::midi::{to_midi, MidiError, MidiOptions};
+use figured_bass::{
+    parse_exercise, solve, Accidental, NoteName, NoteWithOctave, Realisation, Solver, SolverOptions,
+};
+
+fn read_u16(bytes: &[u8]) -> u16 {
+    u16::from_be_bytes([bytes[0], bytes[1]])
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn read_variable_length(bytes: &[u8], i: &mut usize) -> u32 {
+    let mut value = 0;
+    loop {
+        let byte = bytes[*i];
+        *i += 1;
+        value = (value << 7) | (byte & 0x7f) as u32;
+        if byte & 0x80 == 0 {
+            return value;
+        }
+    }
+}
+
+// the (channel, pitch) of every note on event in a track
+fn note_ons(track: &[u8]) -> Vec<(u8, u8)> {
+    let mut notes = Vec::new();
+    let mut i = 0;
+    while i < track.len() {
+        read_variable_length(track, &mut i);
+        let status = track[i];
+        i += 1;
+        match status & 0xf0 {
+            0xf0 => {
+                assert_eq!(status, 0xff);
+                i += 1;
+                let length = read_variable_length(track, &mut i);
+                i += length as usize;
+            }
+            0xc0 => i += 1,
+            0x80 => i += 2,
+            0x90 => {
+                notes.push((status & 0x0f, track[i]));
+                i += 2;
+            }
+            _ => panic!("unexpected status byte {status:#x}"),
+        }
+    }
+    assert_eq!(i, track.len());
+    notes
+}
+
+fn realisation() -> Realisation {
+    let (key_sig, figures) = parse_exercise("key=G major | G2 | C3 6 | D3 7 | G2").unwrap();
+    let options = SolverOptions {
+        solver: Solver::Exact,
+        ..SolverOptions::default()
+    };
+    solve(key_sig, &figures, &options).unwrap()
+}
+
+#[test]
+fn writes_a_header_a_tempo_track_and_a_track_per_voice() {
+    let realisation = realisation();
+    let bytes = to_midi(&realisation, &MidiOptions::default()).unwrap();
+
+    assert_eq!(&bytes[..4], b"MThd");
+    assert_eq!(read_u32(&bytes[4..]), 6);
+    // format 1, five tracks, 480 ticks per beat
+    assert_eq!(read_u16(&bytes[8..]), 1);
+    assert_eq!(read_u16(&bytes[10..]), 5);
+    assert_eq!(read_u16(&bytes[12..]), 480);
+
+    let mut tracks = Vec::new();
+    let mut i = 14;
+    while i < bytes.len() {
+        assert_eq!(&bytes[i..i + 4], b"MTrk");
+        let length = read_u32(&bytes[i + 4..]) as usize;
+        tracks.push(&bytes[i + 8..i + 8 + length]);
+        i += 8 + length;
+    }
+    assert_eq!(i, bytes.len());
+    assert_eq!(tracks.len(), 5);
+
+    assert!(note_ons(tracks[0]).is_empty());
+    for (voice, track) in tracks[1..].iter().enumerate() {
+        let expected: Vec<(u8, u8)> = realisation
+            .chords
+            .iter()
+            .map(|chord| {
+                let pitch = chord.voices()[voice].to_semitones() + 12;
+                (voice as u8, pitch as u8)
+            })
+            .collect();
+        assert_eq!(note_ons(track), expected);
+    }
+}
+
+#[test]
+fn tempos_too_slow_to_write_are_errors() {
+    let realisation = realisation();
+    for tempo in [0, 1, 3] {
+        let options = MidiOptions {
+            tempo,
+            ..MidiOptions::default()
+        };
+        assert_eq!(
+            to_midi(&realisation, &options),
+            Err(MidiError::InvalidTempo(tempo))
+        );
+    }
+    let options = MidiOptions {
+        tempo: 4,
+        ..MidiOptions::default()
+    };
+    let bytes = to_midi(&realisation, &options).unwrap();
+    // the tempo is the first event of the first track, after its name
+    let tempo_event = [0x00, 0xff, 0x51, 0x03, 0xe4, 0xe1, 0xc0];
+    assert!(bytes.windows(7).any(|x| x == tempo_event));
+}
+
+#[test]
+fn notes_above_g9_are_errors() {
+    let mut realisation = realisation();
+    realisation.chords[1].s = NoteWithOctave::new(NoteName::G, None, 9);
+    assert!(to_midi(&realisation, &MidiOptions::default()).is_ok());
+    let note = NoteWithOctave::new(NoteName::G, Some(Accidental::Sharp), 9);
+    realisation.chords[1].s = note;
+    assert_eq!(
+        to_midi(&realisation, &MidiOptions::default()),
+        Err(MidiError::NoteOutOfRange {
+            chord_index: 1,
+            note
+        })
+    );
+}
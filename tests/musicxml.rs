@@ -0,0 +1,25 @@
+use figured_bass::musicxml::{from_musicxml, to_musicxml};
+use figured_bass::{parse_exercise, solve, Solver, SolverOptions};
+
+#[test]
+fn exported_exercises_read_back_the_same() {
+    let options = SolverOptions {
+        solver: Solver::Exact,
+        ..SolverOptions::default()
+    };
+    for exercise in [
+        "key=G major | E3 | F#3 6# | G3 6 | D#3 6 | E3 | B2 #3 | C3 | B2 6/4 | B2 7/#",
+        "key=Eb major | Eb3 | Ab2 6/4 | Bb2 7 | Eb3",
+        "key=D dorian | D3 | C3 6 | Bb2 6/4/2 | A2 #",
+        "key=C# minor | C#3 | B#2 6/5 | C#3",
+    ] {
+        let (key_sig, figures) = parse_exercise(exercise).unwrap();
+        let realisation = solve(key_sig, &figures, &options).unwrap();
+        let xml = to_musicxml(&realisation, &figures, key_sig);
+        assert_eq!(
+            from_musicxml(&xml).unwrap(),
+            (key_sig, figures),
+            "{exercise}"
+        );
+    }
+}
@@ -0,0 +1,179 @@
+use figured_bass::{
+    parse_exercise, Accidental, Figure, KeySignature, NoteName, ParseError, Tonality,
+};
+
+fn error(input: &str) -> ParseError {
+    parse_exercise(input).unwrap_err()
+}
+
+#[test]
+fn parses_bass_notes_and_figures() {
+    let (key_sig, figures) =
+        parse_exercise("key=G major\nE3 | F#3 6# | G3 6\nD#3 6 | E3 | B2 #").unwrap();
+    assert_eq!(
+        key_sig,
+        KeySignature::from_fifths(1, Tonality::Major).unwrap()
+    );
+    assert_eq!(
+        figures,
+        [
+            Figure::new(NoteName::E, None, 3, vec![]),
+            Figure::new(
+                NoteName::F,
+                Some(Accidental::Sharp),
+                3,
+                vec![(6, Some(Accidental::Sharp))]
+            ),
+            Figure::new(NoteName::G, None, 3, vec![(6, None)]),
+            Figure::new(NoteName::D, Some(Accidental::Sharp), 3, vec![(6, None)]),
+            Figure::new(NoteName::E, None, 3, vec![]),
+            Figure::new(NoteName::B, None, 2, vec![(3, Some(Accidental::Sharp))]),
+        ]
+    );
+}
+
+#[test]
+fn parses_stacked_figures_and_accidentals() {
+    let (key_sig, figures) =
+        parse_exercise("key=Bb minor | C3 6/4 | Db3 #6/4/b3 | Ebb2 7 n5 | Fx3 ## | Gn2 bb")
+            .unwrap();
+    assert_eq!(
+        key_sig,
+        KeySignature::from_fifths(-5, Tonality::Minor).unwrap()
+    );
+    assert_eq!(
+        figures,
+        [
+            Figure::new(NoteName::C, None, 3, vec![(4, None), (6, None)]),
+            Figure::new(
+                NoteName::D,
+                Some(Accidental::Flat),
+                3,
+                vec![
+                    (3, Some(Accidental::Flat)),
+                    (4, None),
+                    (6, Some(Accidental::Sharp))
+                ]
+            ),
+            Figure::new(
+                NoteName::E,
+                Some(Accidental::DoubleFlat),
+                2,
+                vec![(5, Some(Accidental::Natural)), (7, None)]
+            ),
+            Figure::new(
+                NoteName::F,
+                Some(Accidental::DoubleSharp),
+                3,
+                vec![(3, Some(Accidental::DoubleSharp))]
+            ),
+            Figure::new(
+                NoteName::G,
+                Some(Accidental::Natural),
+                2,
+                vec![(3, Some(Accidental::DoubleFlat))]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn key_signature_errors() {
+    assert_eq!(
+        error("G major | C3"),
+        ParseError::new(
+            1,
+            1,
+            "expected a key signature like `key=G major`, found `G`"
+        )
+    );
+    assert_eq!(
+        error("key=H major | C3"),
+        ParseError::new(1, 5, "invalid key note `H`")
+    );
+    assert_eq!(
+        error("key=G majr | C3"),
+        ParseError::new(
+            1,
+            7,
+            "expected `major`, `minor` or a mode like `dorian`, found `majr`"
+        )
+    );
+    assert_eq!(
+        error("key=G | C3"),
+        ParseError::new(
+            1,
+            6,
+            "expected `major`, `minor` or a mode like `dorian` after the key note"
+        )
+    );
+    assert_eq!(
+        error("key=G major please | C3"),
+        ParseError::new(1, 13, "unexpected `please` after the key signature")
+    );
+    assert_eq!(
+        error("key=G# major | C3"),
+        ParseError::new(1, 5, "unsupported key `G#`")
+    );
+    assert_eq!(
+        error("key=G major\nC3 | key=C major"),
+        ParseError::new(2, 6, "the key signature can only be given once")
+    );
+    assert_eq!(
+        error("\n\n"),
+        ParseError::new(2, 1, "expected a key signature like `key=G major`")
+    );
+}
+
+#[test]
+fn bass_note_errors() {
+    assert_eq!(
+        error("key=C major | H3"),
+        ParseError::new(1, 15, "invalid bass note `H3`")
+    );
+    assert_eq!(
+        error("key=C major\nC3 | D"),
+        ParseError::new(2, 7, "missing octave for bass note `D`")
+    );
+    assert_eq!(
+        error("key=C major | C9"),
+        ParseError::new(1, 16, "invalid octave `9`")
+    );
+    assert_eq!(
+        error("key=C major\nC3 | Cb0"),
+        ParseError::new(2, 6, "bass note `Cb0` is below C0")
+    );
+    assert!(parse_exercise("key=C major | C0 | B#0 | Cb1").is_ok());
+    assert_eq!(
+        error("key=C major | C#x"),
+        ParseError::new(1, 17, "invalid octave `x`")
+    );
+    assert_eq!(
+        error("key=C major"),
+        ParseError::new(
+            1,
+            1,
+            "expected at least one bass note after the key signature"
+        )
+    );
+}
+
+#[test]
+fn figure_errors() {
+    assert_eq!(
+        error("key=C major | C3 6/y"),
+        ParseError::new(1, 20, "invalid figure `y`")
+    );
+    assert_eq!(
+        error("key=C major\nC3 | D3 1"),
+        ParseError::new(2, 9, "invalid figure `1`")
+    );
+    assert_eq!(
+        error("key=C major | C3 #6#"),
+        ParseError::new(1, 18, "invalid figure `#6#`")
+    );
+    assert_eq!(
+        error("key=C major | C3 6//4"),
+        ParseError::new(1, 20, "invalid figure ``")
+    );
+}
@@ -0,0 +1,169 @@
+use figured_bass::dynamic_programming::best_realisation;
+use figured_bass::{
+    compare_solvers, parse_exercise, solve, solve_with_rng, Chord, ChordInput, MinorScale,
+    Realisation, Scorer, SolveError, Solver, SolverOptions,
+};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+const EXERCISE: &str = "key=G major | G2 | C3 6 | D3 7 | G2";
+
+#[test]
+fn invalid_genetic_options_are_errors() {
+    let (key_sig, figures) = parse_exercise(EXERCISE).unwrap();
+    let invalid = [
+        SolverOptions {
+            generations: 0,
+            ..SolverOptions::default()
+        },
+        SolverOptions {
+            population: 0,
+            ..SolverOptions::default()
+        },
+        SolverOptions {
+            mutate_thrice_percentage: 0.5,
+            ..SolverOptions::default()
+        },
+    ];
+    for options in invalid {
+        assert!(matches!(
+            solve(key_sig, &figures, &options),
+            Err(SolveError::InvalidOptions(_))
+        ));
+        assert!(matches!(
+            compare_solvers(key_sig, &figures, &options),
+            Err(SolveError::InvalidOptions(_))
+        ));
+    }
+
+    // the exact solver doesn't use them
+    let options = SolverOptions {
+        solver: Solver::Exact,
+        generations: 0,
+        population: 0,
+        ..SolverOptions::default()
+    };
+    assert!(solve(key_sig, &figures, &options).is_ok());
+}
+
+fn small_options() -> SolverOptions {
+    SolverOptions {
+        generations: 10,
+        population: 200,
+        ..SolverOptions::default()
+    }
+}
+
+#[test]
+fn the_same_seed_gives_the_same_realisation() {
+    let (key_sig, figures) = parse_exercise(EXERCISE).unwrap();
+    for seed in [1, 2, 3] {
+        let options = SolverOptions {
+            seed: Some(seed),
+            ..small_options()
+        };
+        let first = solve(key_sig, &figures, &options).unwrap();
+        let second = solve(key_sig, &figures, &options).unwrap();
+        assert_eq!(first, second, "seed {seed}");
+    }
+}
+
+#[test]
+fn the_same_rng_gives_the_same_realisation() {
+    let (key_sig, figures) = parse_exercise(EXERCISE).unwrap();
+    let options = small_options();
+    for seed in [1, 2, 3] {
+        let first = solve_with_rng(
+            &mut StdRng::seed_from_u64(seed),
+            key_sig,
+            &figures,
+            &options,
+        )
+        .unwrap();
+        let second = solve_with_rng(
+            &mut StdRng::seed_from_u64(seed),
+            key_sig,
+            &figures,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(first, second, "seed {seed}");
+    }
+}
+
+#[test]
+fn solve_with_rng_checks_the_options() {
+    let (key_sig, figures) = parse_exercise(EXERCISE).unwrap();
+    let options = SolverOptions {
+        generations: 0,
+        ..SolverOptions::default()
+    };
+    let result = solve_with_rng(&mut StdRng::seed_from_u64(1), key_sig, &figures, &options);
+    assert!(matches!(result, Err(SolveError::InvalidOptions(_))));
+}
+
+// tries every combination of voicings, which is only possible for a few chords
+fn brute_force_score(chords_input: &[ChordInput], scorer: &Scorer) -> i32 {
+    let all_voicings = Realisation::all_voicings(chords_input).unwrap();
+    let mut best = i32::MIN;
+    let mut indices = vec![0; all_voicings.len()];
+    loop {
+        let chords: Vec<Chord> = indices
+            .iter()
+            .zip(all_voicings.iter())
+            .map(|(i, voicings)| voicings[*i])
+            .collect();
+        best = best.max(scorer.score(chords_input, &chords));
+
+        // move on to the next combination, like counting with a digit per chord
+        let mut i = indices.len();
+        loop {
+            if i == 0 {
+                return best;
+            }
+            i -= 1;
+            indices[i] += 1;
+            if indices[i] < all_voicings[i].len() {
+                break;
+            }
+            indices[i] = 0;
+        }
+    }
+}
+
+#[test]
+fn the_exact_solver_matches_a_brute_force_search() {
+    let scorer = Scorer::default();
+    for exercise in [
+        "key=G major | G2 | C3 6 | D3 7",
+        "key=D minor | D3 | A2 #3 | D3",
+        "key=F major | F2 | Bb2 6/4 | C3 7 | F2",
+    ] {
+        let (key_sig, figures) = parse_exercise(exercise).unwrap();
+        let chords_input = ChordInput::from_figures(&figures, key_sig, MinorScale::Natural);
+        let best = best_realisation(&chords_input, &scorer).unwrap();
+        assert_eq!(
+            best.score,
+            scorer.score(&chords_input, &best.chords),
+            "{exercise}"
+        );
+        assert_eq!(
+            best.score,
+            brute_force_score(&chords_input, &scorer),
+            "{exercise}"
+        );
+    }
+}
+
+#[test]
+fn the_genetic_algorithm_never_beats_the_exact_solver() {
+    let (key_sig, figures) = parse_exercise(EXERCISE).unwrap();
+    for seed in [1, 2, 3] {
+        let options = SolverOptions {
+            seed: Some(seed),
+            ..small_options()
+        };
+        let comparison = compare_solvers(key_sig, &figures, &options).unwrap();
+        assert!(comparison.gap() >= 0, "seed {seed}: {}", comparison.gap());
+    }
+}